@@ -32,14 +32,23 @@ import {
   unregisterInstruction,
 } from "./raw_instructions";
 import {
+  CurveKind,
   MintRecord,
+  NftGatePolicy,
+  RefundKind,
   Registrar,
   Schedule,
   serializePriceSchedule,
   SubRecord,
 } from "./state";
 
-import { getMetadataKeyFromMint } from "./utils";
+import {
+  getConfigKey,
+  getDelegateKey,
+  getMetadataKeyFromMint,
+  getPendingChangeKey,
+  getVaultKey,
+} from "./utils";
 
 /**
  * Mainnet program ID
@@ -62,6 +71,7 @@ const FEE_OWNER = new PublicKey("5D2zKog251d6KPCyFyLMt3KroWwXXPWSgTPyhV22K2gR");
  * @param nftGatedCollection - The public key of the NFT gated collection, or null if not applicable.
  * @param maxNftMint - The maximum number of NFTs that can be minted, or null if not applicable.
  * @param allowRevoke - A boolean indicating whether revoking by `authority` is allowed.
+ * @param ancestorDomains - The parent domains of `domain` up to the top level domain, required for subdomains.
  * @returns A promise that resolves to an array containing the transaction instruction.
  */
 export const createRegistrar = async (
@@ -74,7 +84,8 @@ export const createRegistrar = async (
   feeAccount: PublicKey,
   nftGatedCollection: PublicKey | null,
   maxNftMint: number | null,
-  allowRevoke: boolean
+  allowRevoke: boolean,
+  ancestorDomains: PublicKey[] = []
 ) => {
   const { pubkey } = getDomainKeySync(domain);
   const [registrar] = Registrar.findKey(pubkey, SUB_REGISTER_ID);
@@ -96,13 +107,16 @@ export const createRegistrar = async (
     pubkey,
     domainOwner,
     feePayer,
-    NAME_PROGRAM_ID
+    NAME_PROGRAM_ID,
+    getConfigKey(SUB_REGISTER_ID),
+    ancestorDomains
   );
   return [ix];
 };
 
 /**
  * Closes a subdomain registrar and transfers remaining lamports to a target account.
+ * The registrar vault is closed as well, it must be emptied beforehand.
  * @param connection - The Solana blockchain connection object.
  * @param registrar - The public key of the registrar to close.
  * @param authority - The public key of the current authority of the registrar.
@@ -126,45 +140,87 @@ export const closeRegistrar = async (
     newDomainOwner,
     lamportsTarget,
     authority,
-    NAME_PROGRAM_ID
+    NAME_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+    getVaultKey(registrar, SUB_REGISTER_ID)
   );
   return [ix];
 };
 
 /**
  * Updates the registrar with new parameters.
+ * Edits which can hurt subdomain owners are queued in a pending change when the registrar is timelocked.
  * @param connection - The Solana blockchain connection object.
  * @param registrar - The public key of the registrar to update.
- * @param newAuthority - The new authority public key, if updating.
  * @param newMint - The new mint public key, if updating.
  * @param newFeeAccount - The new fee account public key, if updating. Must be a token account for the current mint.
  * @param newPriceSchedule - The new price schedule array, if updating.
  * @param newMaxNftMint - The new maximum NFT mint count, if updating.
+ * @param newRefundPolicy - The new refund policy, if updating. Refunds require the fee account to be the registrar vault.
+ * @param newBondingCurve - The new bonding curve applied to the price schedule, if updating.
+ * @param newSoulbound - Whether new subdomains are bound to the wallet they are registered to, if updating.
+ * @param newTimelockDelay - The new timelock delay in seconds, if updating. 0 disables the timelock.
+ * @param newAllowRevoke - `false` to disable revocation, which cannot be enabled again.
+ * @param newRevokeExpiryTime - The new delay in seconds before a revoked subdomain can be registered again, if updating. Cannot decrease.
+ * @param newIrrevocablePremiumBps - The new price multiplier in basis points for irrevocable subdomains, if updating. 0 stops offering them.
+ * @param newNftGatedCollection - The new gated NFT collection, `null` to remove it.
+ * @param newNftGatePolicy - The new policy applied to subdomains minted with NFTs outside of the gated collection, if updating.
+ * @param authority - The signer, defaults to the registrar authority. Otherwise a delegate with the `EditPricing` permission.
  * @returns A promise that resolves to an array containing the transaction instruction.
  */
 export const editRegistrar = async (
   connection: Connection,
   registrar: PublicKey,
-  newAuthority: PublicKey | undefined,
   newMint: PublicKey | undefined,
   newFeeAccount: PublicKey | undefined,
   newPriceSchedule: Schedule[] | undefined,
-  newMaxNftMint: number | undefined
+  newMaxNftMint: number | undefined,
+  newRefundPolicy?: { kind: RefundKind; window: bigint },
+  newBondingCurve?: {
+    kind: CurveKind;
+    rateBps: bigint;
+    step: bigint;
+    maxMultiplierBps: bigint;
+  },
+  newSoulbound?: boolean,
+  newTimelockDelay?: bigint,
+  newAllowRevoke?: boolean,
+  newRevokeExpiryTime?: bigint,
+  newIrrevocablePremiumBps?: bigint,
+  newNftGatedCollection?: PublicKey | null,
+  newNftGatePolicy?: NftGatePolicy,
+  authority?: PublicKey
 ) => {
   const obj = await Registrar.retrieve(connection, registrar);
+  const signer = authority ?? obj.authority;
   const ix = new editRegistrarInstruction({
-    newAuthority: newAuthority ? newAuthority.toBuffer() : null,
     newMint: newMint ? newMint.toBuffer() : null,
     newFeeAccount: newFeeAccount ? newFeeAccount.toBuffer() : null,
     newPriceSchedule: newPriceSchedule
       ? Array.from(serializePriceSchedule(newPriceSchedule))
       : null,
-    newMaxNftMint: newMaxNftMint ? newMaxNftMint : null,
+    newMaxNftMint: newMaxNftMint ?? null,
+    newRefundPolicy: newRefundPolicy ?? null,
+    newBondingCurve: newBondingCurve ?? null,
+    newSoulbound: newSoulbound ?? null,
+    newTimelockDelay: newTimelockDelay ?? null,
+    newAllowRevoke: newAllowRevoke ?? null,
+    newRevokeExpiryTime: newRevokeExpiryTime ?? null,
+    newIrrevocablePremiumBps: newIrrevocablePremiumBps ?? null,
+    newNftGatedCollection:
+      newNftGatedCollection === undefined
+        ? null
+        : { value: newNftGatedCollection?.toBuffer() ?? null },
+    newNftGatePolicy: newNftGatePolicy ?? null,
   }).getInstruction(
     SUB_REGISTER_ID,
     SystemProgram.programId,
-    obj.authority,
-    registrar
+    signer,
+    registrar,
+    signer.equals(obj.authority)
+      ? undefined
+      : getDelegateKey(registrar, signer, SUB_REGISTER_ID),
+    getPendingChangeKey(registrar, SUB_REGISTER_ID)
   );
   return [ix];
 };
@@ -176,6 +232,8 @@ export const editRegistrar = async (
  * @param buyer - The public key of the buyer who is registering the subdomain.
  * @param nftAccount - The public key of the NFT account used for gated access, if applicable.
  * @param subDomain - The name of the subdomain being registered.
+ * @param recipient - The owner of the subdomain, defaults to the buyer. Must hold the NFT if the registrar is NFT gated.
+ * @param irrevocable - Whether to register an irrevocable subdomain, priced with the irrevocable premium of the registrar.
  * @returns A promise that resolves to an array containing the transaction instructions.
  */
 export const register = async (
@@ -183,7 +241,9 @@ export const register = async (
   registrar: PublicKey,
   buyer: PublicKey,
   nftAccount: PublicKey,
-  subDomain: string
+  subDomain: string,
+  recipient?: PublicKey,
+  irrevocable = false
 ) => {
   const ixs: TransactionInstruction[] = [];
   const obj = await Registrar.retrieve(connection, registrar);
@@ -221,6 +281,8 @@ export const register = async (
 
   const ix = new registerInstruction({
     domain: `\0`.concat(subDomain),
+    recipient: recipient ? recipient.toBuffer() : null,
+    irrevocable,
   }).getInstruction(
     SUB_REGISTER_ID,
     SystemProgram.programId,
@@ -238,11 +300,16 @@ export const register = async (
     reverseKey,
     buyer,
     bonfidaFee,
+    getConfigKey(SUB_REGISTER_ID),
     subRecord,
     nftAccount,
     nftMetadata,
     nftMintRecord
   );
+  // The recipient owns the subdomain on creation, its account must be passed
+  if (recipient && !recipient.equals(buyer)) {
+    ix.keys.push({ pubkey: recipient, isSigner: false, isWritable: false });
+  }
   ixs.push(ix);
 
   return ixs;
@@ -329,13 +396,19 @@ export const unregister = async (
  * @param registrar - The public key of the registrar responsible for the domain.
  * @param subDomain - The name of the subdomain to register.
  * @param authority - The public key of the administrative authority registering the subdomain.
+ * @param recipient - The owner of the subdomain, defaults to the authority.
+ * @param expiryTimestamp - The timestamp from which anyone can revoke the subdomain, if any.
+ * @param allowRevoke - Restricts the `allowRevoke` setting of the registrar for this subdomain, if any.
  * @returns A promise that resolves to an array containing the transaction instruction for registering the subdomain.
  */
 export const adminRegister = async (
   connection: Connection,
   registrar: PublicKey,
   subDomain: string,
-  authority: PublicKey
+  authority: PublicKey,
+  recipient?: PublicKey,
+  expiryTimestamp?: bigint,
+  allowRevoke?: boolean
 ) => {
   const obj = await Registrar.retrieve(connection, registrar);
   const parent = await reverseLookup(connection, obj.domain);
@@ -345,6 +418,9 @@ export const adminRegister = async (
 
   const ix = new adminRegisterInstruction({
     domain: `\0`.concat(subDomain),
+    recipient: recipient ? recipient.toBuffer() : null,
+    expiryTimestamp: expiryTimestamp ?? null,
+    allowRevoke: allowRevoke ?? null,
   }).getInstruction(
     SUB_REGISTER_ID,
     SystemProgram.programId,
@@ -361,6 +437,10 @@ export const adminRegister = async (
     subRecord,
     authority
   );
+  // The recipient owns the subdomain on creation, its account must be passed
+  if (recipient && !recipient.equals(authority)) {
+    ix.keys.push({ pubkey: recipient, isSigner: false, isWritable: false });
+  }
   return [ix];
 };
 
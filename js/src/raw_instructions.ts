@@ -9,34 +9,92 @@ export interface AccountKey {
 }
 export class editRegistrarInstruction {
   tag: number;
-  newAuthority: Uint8Array | null;
   newMint: Uint8Array | null;
   newFeeAccount: Uint8Array | null;
   newPriceSchedule: number[] | null;
   newMaxNftMint: number | null;
+  newRefundPolicy: { kind: number; window: bigint } | null;
+  newBondingCurve: {
+    kind: number;
+    rateBps: bigint;
+    step: bigint;
+    maxMultiplierBps: bigint;
+  } | null;
+  newSoulbound: boolean | null;
+  newTimelockDelay: bigint | null;
+  newAllowRevoke: boolean | null;
+  newRevokeExpiryTime: bigint | null;
+  newIrrevocablePremiumBps: bigint | null;
+  newNftGatedCollection: { value: Uint8Array | null } | null;
+  newNftGatePolicy: number | null;
   static schema = {
     struct: {
       tag: "u8",
-      newAuthority: { option: { array: { type: "u8", len: 32 } } },
       newMint: { option: { array: { type: "u8", len: 32 } } },
       newFeeAccount: { option: { array: { type: "u8", len: 32 } } },
       newPriceSchedule: { option: { array: { type: "u8" } } },
-      newMaxNftMint: { option: "u8" },
+      newMaxNftMint: { option: "u32" },
+      newRefundPolicy: {
+        option: { struct: { kind: "u8", window: "i64" } },
+      },
+      newBondingCurve: {
+        option: {
+          struct: {
+            kind: "u8",
+            rateBps: "u64",
+            step: "u64",
+            maxMultiplierBps: "u64",
+          },
+        },
+      },
+      newSoulbound: { option: "bool" },
+      newTimelockDelay: { option: "i64" },
+      newAllowRevoke: { option: "bool" },
+      newRevokeExpiryTime: { option: "i64" },
+      newIrrevocablePremiumBps: { option: "u64" },
+      // Option<Option<Pubkey>>, the inner option is wrapped so that `{ value: null }` removes the collection
+      newNftGatedCollection: {
+        option: {
+          struct: { value: { option: { array: { type: "u8", len: 32 } } } },
+        },
+      },
+      newNftGatePolicy: { option: "u8" },
     },
   };
   constructor(obj: {
-    newAuthority: Uint8Array | null;
     newMint: Uint8Array | null;
     newFeeAccount: Uint8Array | null;
     newPriceSchedule: number[] | null;
     newMaxNftMint: number | null;
+    newRefundPolicy: { kind: number; window: bigint } | null;
+    newBondingCurve: {
+      kind: number;
+      rateBps: bigint;
+      step: bigint;
+      maxMultiplierBps: bigint;
+    } | null;
+    newSoulbound: boolean | null;
+    newTimelockDelay: bigint | null;
+    newAllowRevoke: boolean | null;
+    newRevokeExpiryTime: bigint | null;
+    newIrrevocablePremiumBps: bigint | null;
+    newNftGatedCollection: { value: Uint8Array | null } | null;
+    newNftGatePolicy: number | null;
   }) {
     this.tag = 1;
-    this.newAuthority = obj.newAuthority;
     this.newMint = obj.newMint;
     this.newFeeAccount = obj.newFeeAccount;
     this.newPriceSchedule = obj.newPriceSchedule;
     this.newMaxNftMint = obj.newMaxNftMint;
+    this.newRefundPolicy = obj.newRefundPolicy;
+    this.newBondingCurve = obj.newBondingCurve;
+    this.newSoulbound = obj.newSoulbound;
+    this.newTimelockDelay = obj.newTimelockDelay;
+    this.newAllowRevoke = obj.newAllowRevoke;
+    this.newRevokeExpiryTime = obj.newRevokeExpiryTime;
+    this.newIrrevocablePremiumBps = obj.newIrrevocablePremiumBps;
+    this.newNftGatedCollection = obj.newNftGatedCollection;
+    this.newNftGatePolicy = obj.newNftGatePolicy;
   }
   serialize(): Uint8Array {
     return serialize(editRegistrarInstruction.schema, this);
@@ -45,7 +103,9 @@ export class editRegistrarInstruction {
     programId: PublicKey,
     systemProgram: PublicKey,
     authority: PublicKey,
-    registrar: PublicKey
+    registrar: PublicKey,
+    delegate?: PublicKey,
+    pendingChange?: PublicKey
   ): TransactionInstruction {
     const data = Buffer.from(this.serialize());
     let keys: AccountKey[] = [];
@@ -64,6 +124,20 @@ export class editRegistrarInstruction {
       isSigner: false,
       isWritable: true,
     });
+    if (!!delegate) {
+      keys.push({
+        pubkey: delegate,
+        isSigner: false,
+        isWritable: false,
+      });
+    }
+    if (!!pendingChange) {
+      keys.push({
+        pubkey: pendingChange,
+        isSigner: false,
+        isWritable: true,
+      });
+    }
     return new TransactionInstruction({
       keys,
       programId,
@@ -173,7 +247,9 @@ export class closeRegistrarInstruction {
     newDomainOwner: PublicKey,
     lamportsTarget: PublicKey,
     registryAuthority: PublicKey,
-    splNameProgramId: PublicKey
+    splNameProgramId: PublicKey,
+    splTokenProgram: PublicKey,
+    vault: PublicKey
   ): TransactionInstruction {
     const data = Buffer.from(this.serialize());
     let keys: AccountKey[] = [];
@@ -212,6 +288,16 @@ export class closeRegistrarInstruction {
       isSigner: false,
       isWritable: false,
     });
+    keys.push({
+      pubkey: splTokenProgram,
+      isSigner: false,
+      isWritable: false,
+    });
+    keys.push({
+      pubkey: vault,
+      isSigner: false,
+      isWritable: true,
+    });
     return new TransactionInstruction({
       keys,
       programId,
@@ -222,15 +308,25 @@ export class closeRegistrarInstruction {
 export class registerInstruction {
   tag: number;
   domain: string;
+  recipient: Uint8Array | null;
+  irrevocable: boolean;
   static schema = {
     struct: {
       tag: "u8",
       domain: "string",
+      recipient: { option: { array: { type: "u8", len: 32 } } },
+      irrevocable: "bool",
     },
   };
-  constructor(obj: { domain: string }) {
+  constructor(obj: {
+    domain: string;
+    recipient: Uint8Array | null;
+    irrevocable: boolean;
+  }) {
     this.tag = 2;
     this.domain = obj.domain;
+    this.recipient = obj.recipient;
+    this.irrevocable = obj.irrevocable;
   }
   serialize(): Uint8Array {
     return serialize(registerInstruction.schema, this);
@@ -252,6 +348,7 @@ export class registerInstruction {
     subReverseAccount: PublicKey,
     feePayer: PublicKey,
     bonfidaFeeAccount: PublicKey,
+    config: PublicKey,
     subRecord: PublicKey,
     nftAccount?: PublicKey,
     nftMetadataAccount?: PublicKey,
@@ -334,6 +431,11 @@ export class registerInstruction {
       isSigner: false,
       isWritable: true,
     });
+    keys.push({
+      pubkey: config,
+      isSigner: false,
+      isWritable: false,
+    });
     keys.push({
       pubkey: subRecord,
       isSigner: false,
@@ -385,7 +487,7 @@ export class createRegistrarInstruction {
       authority: { array: { type: "u8", len: 32 } },
       priceSchedule: { array: { type: "u8" } },
       nftGatedCollection: { option: { array: { type: "u8", len: 32 } } },
-      maxNftMint: "u8",
+      maxNftMint: "u32",
       allowRevoke: "bool",
       revokeExpiryDelay: "i64",
     },
@@ -420,7 +522,9 @@ export class createRegistrarInstruction {
     domainNameAccount: PublicKey,
     domainOwner: PublicKey,
     feePayer: PublicKey,
-    splNameProgramId: PublicKey
+    splNameProgramId: PublicKey,
+    config: PublicKey,
+    ancestorDomains: PublicKey[] = []
   ): TransactionInstruction {
     const data = Buffer.from(this.serialize());
     let keys: AccountKey[] = [];
@@ -454,6 +558,18 @@ export class createRegistrarInstruction {
       isSigner: false,
       isWritable: false,
     });
+    keys.push({
+      pubkey: config,
+      isSigner: false,
+      isWritable: false,
+    });
+    for (const ancestor of ancestorDomains) {
+      keys.push({
+        pubkey: ancestor,
+        isSigner: false,
+        isWritable: false,
+      });
+    }
     return new TransactionInstruction({
       keys,
       programId,
@@ -464,15 +580,29 @@ export class createRegistrarInstruction {
 export class adminRegisterInstruction {
   tag: number;
   domain: string;
+  recipient: Uint8Array | null;
+  expiryTimestamp: bigint | null;
+  allowRevoke: boolean | null;
   static schema = {
     struct: {
       tag: "u8",
       domain: "string",
+      recipient: { option: { array: { type: "u8", len: 32 } } },
+      expiryTimestamp: { option: "i64" },
+      allowRevoke: { option: "bool" },
     },
   };
-  constructor(obj: { domain: string }) {
+  constructor(obj: {
+    domain: string;
+    recipient: Uint8Array | null;
+    expiryTimestamp: bigint | null;
+    allowRevoke: boolean | null;
+  }) {
     this.tag = 5;
     this.domain = obj.domain;
+    this.recipient = obj.recipient;
+    this.expiryTimestamp = obj.expiryTimestamp;
+    this.allowRevoke = obj.allowRevoke;
   }
   serialize(): Uint8Array {
     return serialize(adminRegisterInstruction.schema, this);
@@ -6,19 +6,27 @@ import { Tag } from "./tag";
 export class MintRecord {
   static SEED = "nft_mint_record";
   tag: Tag;
+  version: number;
   count: number;
   mint: PublicKey;
 
   static schema = {
     struct: {
       tag: "u8",
-      count: "u8",
+      version: "u8",
+      count: "u32",
       mint: { array: { type: "u8", len: 32 } },
     },
   };
 
-  constructor(obj: { tag: bigint; count: number; mint: Uint8Array }) {
+  constructor(obj: {
+    tag: bigint;
+    version: number;
+    count: number;
+    mint: Uint8Array;
+  }) {
     this.tag = Number(obj.tag) as Tag;
+    this.version = obj.version;
     this.count = obj.count;
     this.mint = new PublicKey(obj.mint);
   }
@@ -1,6 +1,6 @@
 import { deserialize, serialize } from "borsh";
 import { Connection, MemcmpFilter, PublicKey } from "@solana/web3.js";
-import { Tag } from "./tag";
+import { encodeTag, Tag } from "./tag";
 import { SUB_REGISTER_ID } from "../";
 
 export class Schedule {
@@ -17,9 +17,69 @@ export class Schedule {
   }
 }
 
+// The refund applied when a subdomain is unregistered within the refund window
+export enum RefundKind {
+  None,
+  Full,
+  ProRata,
+}
+
+// How the price schedule scales with the number of active subdomains
+export enum CurveKind {
+  None,
+  Linear,
+  Stepped,
+  Exponential,
+}
+
+// What happens to the subdomains minted with an NFT outside of the gated collection
+export enum NftGatePolicy {
+  Grandfathered,
+  Revocable,
+}
+
+export interface RefundPolicy {
+  kind: RefundKind;
+  // The refund window in seconds, starting at the registration
+  window: bigint;
+}
+
+export interface BondingCurve {
+  kind: CurveKind;
+  rateBps: bigint;
+  step: bigint;
+  maxMultiplierBps: bigint;
+}
+
+// The TLD root of a registrar domain
+export class RootDomain {
+  nameAccount: PublicKey;
+  registrarProgram: PublicKey;
+  reverseLookupClass: PublicKey;
+
+  static schema = {
+    struct: {
+      nameAccount: { array: { type: "u8", len: 32 } },
+      registrarProgram: { array: { type: "u8", len: 32 } },
+      reverseLookupClass: { array: { type: "u8", len: 32 } },
+    },
+  };
+
+  constructor(obj: {
+    nameAccount: Uint8Array;
+    registrarProgram: Uint8Array;
+    reverseLookupClass: Uint8Array;
+  }) {
+    this.nameAccount = new PublicKey(obj.nameAccount);
+    this.registrarProgram = new PublicKey(obj.registrarProgram);
+    this.reverseLookupClass = new PublicKey(obj.reverseLookupClass);
+  }
+}
+
 export class Registrar {
   static SEED = "registrar";
   tag: Tag;
+  version: number;
   nonce: number;
   authority: PublicKey;
   feeAccount: PublicKey;
@@ -30,10 +90,22 @@ export class Registrar {
   maxNftMint: number;
   allowRevoke: boolean;
   priceSchedule: Schedule[];
+  revokeExpiryTime: bigint;
+  refundPolicy: RefundPolicy;
+  bondingCurve: BondingCurve;
+  soulbound: boolean;
+  pendingAuthority: PublicKey | null;
+  timelockDelay: bigint;
+  irrevocablePremiumBps: bigint;
+  nftGatePolicy: NftGatePolicy;
+  windDownDeadline: bigint | null;
+  createdAt: bigint;
+  rootDomain: RootDomain;
 
   static schema = {
     struct: {
       tag: "u8",
+      version: "u8",
       nonce: "u8",
       authority: { array: { type: "u8", len: 32 } },
       feeAccount: { array: { type: "u8", len: 32 } },
@@ -41,14 +113,33 @@ export class Registrar {
       domain: { array: { type: "u8", len: 32 } },
       totalSubCreated: "u64",
       nftGatedCollection: { option: { array: { type: "u8", len: 32 } } },
-      maxNftMint: "u8",
-      allowRevoke: "u8",
+      maxNftMint: "u32",
+      allowRevoke: "bool",
       priceSchedule: { array: { type: Schedule.schema } },
+      revokeExpiryTime: "i64",
+      refundPolicy: { struct: { kind: "u8", window: "i64" } },
+      bondingCurve: {
+        struct: {
+          kind: "u8",
+          rateBps: "u64",
+          step: "u64",
+          maxMultiplierBps: "u64",
+        },
+      },
+      soulbound: "bool",
+      pendingAuthority: { option: { array: { type: "u8", len: 32 } } },
+      timelockDelay: "i64",
+      irrevocablePremiumBps: "u64",
+      nftGatePolicy: "u8",
+      windDownDeadline: { option: "i64" },
+      createdAt: "i64",
+      rootDomain: RootDomain.schema,
     },
   };
 
   constructor(obj: {
     tag: Tag;
+    version: number;
     nonce: number;
     authority: Uint8Array;
     feeAccount: Uint8Array;
@@ -59,8 +150,24 @@ export class Registrar {
     maxNftMint: number;
     allowRevoke: boolean;
     priceSchedule: Schedule[];
+    revokeExpiryTime: bigint;
+    refundPolicy: RefundPolicy;
+    bondingCurve: BondingCurve;
+    soulbound: boolean;
+    pendingAuthority: Uint8Array | null;
+    timelockDelay: bigint;
+    irrevocablePremiumBps: bigint;
+    nftGatePolicy: NftGatePolicy;
+    windDownDeadline: bigint | null;
+    createdAt: bigint;
+    rootDomain: {
+      nameAccount: Uint8Array;
+      registrarProgram: Uint8Array;
+      reverseLookupClass: Uint8Array;
+    };
   }) {
     this.tag = obj.tag;
+    this.version = obj.version;
     this.nonce = obj.nonce;
     this.authority = new PublicKey(obj.authority);
     this.feeAccount = new PublicKey(obj.feeAccount);
@@ -73,6 +180,19 @@ export class Registrar {
     this.maxNftMint = obj.maxNftMint;
     this.allowRevoke = obj.allowRevoke;
     this.priceSchedule = obj.priceSchedule;
+    this.revokeExpiryTime = obj.revokeExpiryTime;
+    this.refundPolicy = obj.refundPolicy;
+    this.bondingCurve = obj.bondingCurve;
+    this.soulbound = obj.soulbound;
+    this.pendingAuthority = obj.pendingAuthority
+      ? new PublicKey(obj.pendingAuthority)
+      : null;
+    this.timelockDelay = obj.timelockDelay;
+    this.irrevocablePremiumBps = obj.irrevocablePremiumBps;
+    this.nftGatePolicy = obj.nftGatePolicy;
+    this.windDownDeadline = obj.windDownDeadline;
+    this.createdAt = obj.createdAt;
+    this.rootDomain = new RootDomain(obj.rootDomain);
   }
 
   static deserialize(data: Buffer): Registrar {
@@ -89,8 +209,13 @@ export class Registrar {
 
   static async findForDomain(connection: Connection, domain: PublicKey) {
     const filters: MemcmpFilter[] = [
-      { memcmp: { offset: 1 + 1 + 32 + 32 + 32, bytes: domain.toBase58() } },
-      { memcmp: { offset: 0, bytes: (Tag.Registrar + 1).toString() } },
+      {
+        memcmp: {
+          offset: 1 + 1 + 1 + 32 + 32 + 32,
+          bytes: domain.toBase58(),
+        },
+      },
+      { memcmp: { offset: 0, bytes: encodeTag(Tag.Registrar) } },
     ];
     const accounts = await connection.getProgramAccounts(SUB_REGISTER_ID, {
       filters,
@@ -105,8 +230,8 @@ export class Registrar {
 
   static async findForOwner(connection: Connection, owner: PublicKey) {
     const filters: MemcmpFilter[] = [
-      { memcmp: { offset: 1 + 1, bytes: owner.toBase58() } },
-      { memcmp: { offset: 0, bytes: (Tag.Registrar + 1).toString() } },
+      { memcmp: { offset: 1 + 1 + 1, bytes: owner.toBase58() } },
+      { memcmp: { offset: 0, bytes: encodeTag(Tag.Registrar) } },
     ];
     const accounts = await connection.getProgramAccounts(SUB_REGISTER_ID, {
       filters,
@@ -128,7 +253,7 @@ export class Registrar {
 
   static async findAll(connection: Connection) {
     const filters: MemcmpFilter[] = [
-      { memcmp: { offset: 0, bytes: (Tag.Registrar + 1).toString() } },
+      { memcmp: { offset: 0, bytes: encodeTag(Tag.Registrar) } },
     ];
     const accounts = await connection.getProgramAccounts(SUB_REGISTER_ID, {
       filters,
@@ -1,44 +1,83 @@
 import { deserialize } from "borsh";
-import { Connection, PublicKey } from "@solana/web3.js";
-import { Tag } from "./tag";
+import { Connection, MemcmpFilter, PublicKey } from "@solana/web3.js";
+import { encodeTag, Tag } from "./tag";
+import { SUB_REGISTER_ID } from "../";
 
 // SubRecord are used to keep track of subs minted via a specific registrar
 export class SubRecord {
   static SEED = "subrecord";
   tag: Tag;
+  version: number;
   registrar: PublicKey;
   subKey: PublicKey;
+  owner: PublicKey;
   mintRecord: PublicKey | undefined;
   expiryTimestamp: bigint;
   allocator: PublicKey;
+  registeredAt: bigint;
+  pricePaid: bigint;
+  allowRevoke: boolean | undefined;
+  boundOwner: PublicKey | undefined;
+  lastOwnerSync: bigint;
+  transferCount: bigint;
+  irrevocable: boolean;
 
   static schema = {
     struct: {
       tag: "u8",
+      version: "u8",
       registrar: { array: { type: "u8", len: 32 } },
       subKey: { array: { type: "u8", len: 32 } },
+      owner: { array: { type: "u8", len: 32 } },
       mintRecord: { option: { array: { type: "u8", len: 32 } } },
-      expiryTimestamp: "u64",
+      expiryTimestamp: "i64",
       allocator: { array: { type: "u8", len: 32 } },
+      registeredAt: "i64",
+      pricePaid: "u64",
+      allowRevoke: { option: "bool" },
+      boundOwner: { option: { array: { type: "u8", len: 32 } } },
+      lastOwnerSync: "i64",
+      transferCount: "u64",
+      irrevocable: "bool",
     },
   };
 
   constructor(obj: {
     tag: number;
+    version: number;
     registrar: Uint8Array;
     subKey: Uint8Array;
+    owner: Uint8Array;
     mintRecord: Uint8Array | null;
     expiryTimestamp: bigint;
     allocator: Uint8Array;
+    registeredAt: bigint;
+    pricePaid: bigint;
+    allowRevoke: boolean | null;
+    boundOwner: Uint8Array | null;
+    lastOwnerSync: bigint;
+    transferCount: bigint;
+    irrevocable: boolean;
   }) {
     this.tag = obj.tag as Tag;
+    this.version = obj.version;
     this.registrar = new PublicKey(obj.registrar);
     this.subKey = new PublicKey(obj.subKey);
+    this.owner = new PublicKey(obj.owner);
     this.mintRecord = obj.mintRecord
       ? new PublicKey(obj.mintRecord)
       : undefined;
     this.expiryTimestamp = obj.expiryTimestamp;
     this.allocator = new PublicKey(obj.allocator);
+    this.registeredAt = obj.registeredAt;
+    this.pricePaid = obj.pricePaid;
+    this.allowRevoke = obj.allowRevoke ?? undefined;
+    this.boundOwner = obj.boundOwner
+      ? new PublicKey(obj.boundOwner)
+      : undefined;
+    this.lastOwnerSync = obj.lastOwnerSync;
+    this.transferCount = obj.transferCount;
+    this.irrevocable = obj.irrevocable;
   }
 
   static deserialize(data: Buffer): SubRecord {
@@ -52,6 +91,24 @@ export class SubRecord {
     }
     return this.deserialize(accountInfo.data);
   }
+
+  // The owner is the one recorded at the last `SyncOwner`
+  static async findForOwner(connection: Connection, owner: PublicKey) {
+    const filters: MemcmpFilter[] = [
+      { memcmp: { offset: 1 + 1 + 32 + 32, bytes: owner.toBase58() } },
+      { memcmp: { offset: 0, bytes: encodeTag(Tag.SubRecord) } },
+    ];
+    const accounts = await connection.getProgramAccounts(SUB_REGISTER_ID, {
+      filters,
+    });
+    return accounts.map((e) => {
+      return {
+        pubkey: e.pubkey,
+        subRecord: SubRecord.deserialize(e.account.data),
+      };
+    });
+  }
+
   static findKey(domain: PublicKey, programId: PublicKey) {
     return PublicKey.findProgramAddressSync(
       [Buffer.from(SubRecord.SEED), domain.toBuffer()],
@@ -1,8 +1,26 @@
 export enum Tag {
   Uninitialized,
-  Registrar,
+  LegacyRegistrar,
   ClosedRegistrar,
+  LegacySubRecord,
+  LegacyClosedSubRecord,
+  LegacyMintRecord,
+  LegacyRevokedSubRecord,
+  Config,
+  Delegate,
+  PendingChange,
+  Registrar,
+  MintRecord,
   SubRecord,
   ClosedSubRecord,
-  MintRecord,
+  RevokedSubRecord,
 }
+
+const BASE58_ALPHABET =
+  "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/**
+ * Returns the base58 encoded tag byte, to be used in memcmp filters at offset 0
+ * @param tag - The tag of the accounts to filter
+ */
+export const encodeTag = (tag: Tag) => BASE58_ALPHABET[tag];
@@ -11,3 +11,67 @@ export const getMetadataKeyFromMint = (mint: PublicKey) => {
   );
   return key;
 };
+
+const CONFIG_SEEDS = Buffer.from("config");
+
+/**
+ * Returns the key of the global config account of the program
+ * @param programId - The public key of the subdomain registrar program
+ */
+export const getConfigKey = (programId: PublicKey) => {
+  const [key] = PublicKey.findProgramAddressSync([CONFIG_SEEDS], programId);
+  return key;
+};
+
+const DELEGATE_SEEDS = Buffer.from("delegate");
+
+/**
+ * Returns the key of the delegate account of `delegate` for a registrar
+ * @param registrar - The public key of the registrar
+ * @param delegate - The public key of the delegate wallet
+ * @param programId - The public key of the subdomain registrar program
+ */
+export const getDelegateKey = (
+  registrar: PublicKey,
+  delegate: PublicKey,
+  programId: PublicKey
+) => {
+  const [key] = PublicKey.findProgramAddressSync(
+    [DELEGATE_SEEDS, registrar.toBuffer(), delegate.toBuffer()],
+    programId
+  );
+  return key;
+};
+
+const PENDING_CHANGE_SEEDS = Buffer.from("pending_change");
+
+/**
+ * Returns the key of the account holding the timelocked change of a registrar
+ * @param registrar - The public key of the registrar
+ * @param programId - The public key of the subdomain registrar program
+ */
+export const getPendingChangeKey = (
+  registrar: PublicKey,
+  programId: PublicKey
+) => {
+  const [key] = PublicKey.findProgramAddressSync(
+    [PENDING_CHANGE_SEEDS, registrar.toBuffer()],
+    programId
+  );
+  return key;
+};
+
+const VAULT_SEEDS = Buffer.from("vault");
+
+/**
+ * Returns the key of the token account collecting the proceeds of a registrar
+ * @param registrar - The public key of the registrar
+ * @param programId - The public key of the subdomain registrar program
+ */
+export const getVaultKey = (registrar: PublicKey, programId: PublicKey) => {
+  const [key] = PublicKey.findProgramAddressSync(
+    [VAULT_SEEDS, registrar.toBuffer()],
+    programId
+  );
+  return key;
+};
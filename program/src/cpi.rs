@@ -87,9 +87,11 @@ impl Cpi {
         Ok(())
     }
 
+    // Transfers a subdomain to `new_owner`, signed by the registrar as parent domain owner
     pub fn transfer_subdomain<'a>(
         registrar: &Registrar,
         registrar_account: &AccountInfo<'a>,
+        new_owner: &Pubkey,
         sub_account: &AccountInfo<'a>,
         parent_account: &AccountInfo<'a>,
         name_class: &AccountInfo<'a>,
@@ -97,7 +99,7 @@ impl Cpi {
     ) -> Result<(), ProgramError> {
         let mut ix = spl_name_service::instruction::transfer(
             spl_name_service::ID,
-            *registrar_account.key,
+            *new_owner,
             *sub_account.key,
             *registrar_account.key,
             None,
@@ -82,6 +82,42 @@ impl PrintProgramError for SubRegisterError {
             SubRegisterError::WrongMint => {
                 msg!("[+] Error: Wrong mint")
             }
+            SubRegisterError::InvalidFee => {
+                msg!("[+] Error: Invalid fee")
+            }
+            SubRegisterError::SubdomainNotExpired => {
+                msg!("[+] Error: The subdomain has not expired yet")
+            }
+            SubRegisterError::InvalidPendingAuthority => {
+                msg!("[+] Error: Invalid pending authority")
+            }
+            SubRegisterError::MissingPermission => {
+                msg!("[+] Error: The signer is not allowed to perform this action")
+            }
+            SubRegisterError::TimelockNotExpired => {
+                msg!("[+] Error: The timelock of the change has not expired yet")
+            }
+            SubRegisterError::AccountNeedsMigration => {
+                msg!("[+] Error: The account must be migrated first")
+            }
+            SubRegisterError::RegistrarWindingDown => {
+                msg!("[+] Error: The registrar is winding down")
+            }
+            SubRegisterError::WindDownDelayTooLow => {
+                msg!("[+] Error: The proposed wind-down deadline is too soon")
+            }
+            SubRegisterError::StaleSubRecord => {
+                msg!("[+] Error: The subdomain record belongs to a previous registrar")
+            }
+            SubRegisterError::RootDomainNotAllowed => {
+                msg!("[+] Error: The root domain is not allowed")
+            }
+            SubRegisterError::RefundRequiresVault => {
+                msg!("[+] Error: Refunds require the proceeds to be collected in the registrar vault")
+            }
+            SubRegisterError::VaultNotEmpty => {
+                msg!("[+] Error: The registrar vault must be emptied before closing the registrar")
+            }
         }
     }
 }
@@ -50,6 +50,30 @@ pub enum SubRegisterError {
     RevokeExpiryDelayTooLow,
     #[error("Wrong mint")]
     WrongMint,
+    #[error("Invalid fee")]
+    InvalidFee,
+    #[error("The subdomain has not expired yet")]
+    SubdomainNotExpired,
+    #[error("Invalid pending authority")]
+    InvalidPendingAuthority,
+    #[error("The signer is not allowed to perform this action")]
+    MissingPermission,
+    #[error("The timelock of the change has not expired yet")]
+    TimelockNotExpired,
+    #[error("The account must be migrated first")]
+    AccountNeedsMigration,
+    #[error("The registrar is winding down")]
+    RegistrarWindingDown,
+    #[error("The proposed wind-down deadline is too soon")]
+    WindDownDelayTooLow,
+    #[error("The subdomain record belongs to a previous registrar")]
+    StaleSubRecord,
+    #[error("The root domain is not allowed")]
+    RootDomainNotAllowed,
+    #[error("Refunds require the proceeds to be collected in the registrar vault")]
+    RefundRequiresVault,
+    #[error("The registrar vault must be emptied before closing the registrar")]
+    VaultNotEmpty,
 }
 
 impl From<SubRegisterError> for ProgramError {
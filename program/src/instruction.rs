@@ -1,6 +1,10 @@
 pub use crate::processor::{
-    admin_register, admin_revoke, close_registrar, create_registrar, delete_subdomain_record,
-    edit_registrar, nft_owner_revoke, register, unregister,
+    accept_authority, admin_register, admin_register_batch, admin_revoke, admin_revoke_batch,
+    admin_transfer, cancel_authority_transfer, cancel_change, close_registrar, create_config,
+    create_registrar, create_vault, delete_subdomain_record, edit_config, edit_registrar,
+    execute_change, migrate, migrate_registrar, nft_owner_revoke, propose_authority, quote_price,
+    register, revoke_expired, set_delegate, set_irrevocable, sync_owner, unregister, wind_down,
+    withdraw,
 };
 use {
     bonfida_utils::InstructionsAccount,
@@ -13,34 +17,41 @@ use {
 pub enum ProgramInstruction {
     /// Create registrar
     ///
-    /// | Index | Writable | Signer | Description                          |
-    /// | ---------------------------------------------------------------- |
-    /// | 0     | ❌        | ❌      | The system program account           |
-    /// | 1     | ✅        | ❌      | The registrar account                |
-    /// | 2     | ✅        | ❌      | The domain account                   |
-    /// | 3     | ✅        | ✅      | The owner of the domain name account |
-    /// | 4     | ✅        | ✅      | The fee payer account                |
-    /// | 5     | ❌        | ❌      | The SPL name service program ID      |
+    /// | Index | Writable | Signer | Description                                                                          |
+    /// | ---------------------------------------------------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account                                                           |
+    /// | 1     | ✅        | ❌      | The registrar account                                                                |
+    /// | 2     | ✅        | ❌      | The domain account                                                                   |
+    /// | 3     | ✅        | ✅      | The owner of the domain name account                                                 |
+    /// | 4     | ✅        | ✅      | The fee payer account                                                                |
+    /// | 5     | ❌        | ❌      | The SPL name service program ID                                                      |
+    /// | 6     | ❌        | ❌      | The global config account, may not be initialized                                    |
+    /// | 7     | ❌        | ❌      | The parent domains of the domain up to the top level domain, required for subdomains |
     CreateRegistrar,
     /// Edit a registrar
     ///
-    /// | Index | Writable | Signer | Description                |
-    /// | ------------------------------------------------------ |
-    /// | 0     | ❌        | ❌      | The system program account |
-    /// | 1     | ✅        | ✅      | The fee payer account      |
-    /// | 2     | ✅        | ❌      | The registry to edit       |
+    /// | Index | Writable | Signer | Description                                                              |
+    /// | ---------------------------------------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account                                               |
+    /// | 1     | ✅        | ✅      | The registrar authority or a delegate with the `EditPricing` permission  |
+    /// | 2     | ✅        | ❌      | The registry to edit                                                     |
+    /// | 3     | ❌        | ❌      | The delegate account if the signer is not the registrar authority        |
+    /// | 4     | ✅        | ❌      | The pending change account, required when sensitive edits are timelocked |
     EditRegistrar,
     /// Register a subdomain
     ///
+    /// The global config account was inserted at index 15, before the optional NFT accounts:
+    /// clients passing the subrecord at index 15 must be updated
+    ///
     /// | Index | Writable | Signer | Description                                                                           |
     /// | ----------------------------------------------------------------------------------------------------------------- |
     /// | 0     | ❌        | ❌      | The system program account                                                            |
     /// | 1     | ❌        | ❌      | The SPL token program account                                                         |
     /// | 2     | ❌        | ❌      | The SPL name service program account                                                  |
     /// | 3     | ❌        | ❌      | The rent sysvar account                                                               |
-    /// | 4     | ❌        | ❌      | The name auctioning program account                                                   |
-    /// | 5     | ❌        | ❌      | The .sol root domain                                                                  |
-    /// | 6     | ❌        | ❌      | The reverse lookup class accoutn                                                      |
+    /// | 4     | ❌        | ❌      | The program creating the reverse lookups of the TLD root                              |
+    /// | 5     | ❌        | ❌      | The TLD root domain of the registrar                                                  |
+    /// | 6     | ❌        | ❌      | The reverse lookup class of the TLD root                                              |
     /// | 7     | ✅        | ❌      | The fee account of the registry                                                       |
     /// | 8     | ✅        | ❌      |                                                                                       |
     /// | 9     | ✅        | ❌      |                                                                                       |
@@ -49,52 +60,59 @@ pub enum ProgramInstruction {
     /// | 12    | ✅        | ❌      |                                                                                       |
     /// | 13    | ✅        | ✅      | The fee payer account                                                                 |
     /// | 14    | ✅        | ❌      |                                                                                       |
-    /// | 15    | ✅        | ❌      | The subrecord account                                                                 |
-    /// | 16    | ❌        | ❌      | Optional NFT account if Registrar is NFT gated                                        |
-    /// | 17    | ❌        | ❌      | Optional NFT metadata account if Registrar is NFT gated                               |
-    /// | 18    | ✅        | ❌      | Optional NFT mint record to keep track of how many domains were created with this NFT |
+    /// | 15    | ❌        | ❌      | The global config account, may not be initialized, required before the subrecord      |
+    /// | 16    | ✅        | ❌      | The subrecord account                                                                 |
+    /// | 17    | ❌        | ❌      | Optional NFT account if Registrar is NFT gated                                        |
+    /// | 18    | ❌        | ❌      | Optional NFT metadata account if Registrar is NFT gated                               |
+    /// | 19    | ✅        | ❌      | Optional NFT mint record to keep track of how many domains were created with this NFT |
     Register,
     /// Unregister a subdomain
     ///
-    /// | Index | Writable | Signer | Description                          |
-    /// | ---------------------------------------------------------------- |
-    /// | 0     | ❌        | ❌      | The system program account           |
-    /// | 1     | ❌        | ❌      | The SPL name service program account |
-    /// | 2     | ✅        | ❌      | The registrar account                |
-    /// | 3     | ✅        | ❌      | The subdomain account to unregister  |
-    /// | 4     | ✅        | ❌      | The subrecord account                |
-    /// | 5     | ✅        | ✅      | The fee payer account                |
-    /// | 6     | ✅        | ❌      |                                      |
+    /// | Index | Writable | Signer | Description                                                       |
+    /// | --------------------------------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account                                        |
+    /// | 1     | ❌        | ❌      | The SPL name service program account                              |
+    /// | 2     | ✅        | ❌      | The registrar account                                             |
+    /// | 3     | ✅        | ❌      | The subdomain account to unregister                               |
+    /// | 4     | ✅        | ❌      | The subrecord account                                             |
+    /// | 5     | ✅        | ✅      | The fee payer account                                             |
+    /// | 6     | ✅        | ❌      |                                                                   |
+    /// | 7     | ❌        | ❌      | Optional SPL token program account if the subdomain is refundable |
+    /// | 8     | ✅        | ❌      | Optional registrar vault if the subdomain is refundable           |
+    /// | 9     | ✅        | ❌      | Optional token account of the domain owner receiving the refund   |
     Unregister,
     /// Close a registrar account
     ///
-    /// | Index | Writable | Signer | Description                              |
-    /// | -------------------------------------------------------------------- |
-    /// | 0     | ❌        | ❌      | The system program account               |
-    /// | 1     | ✅        | ❌      | The registrar account                    |
-    /// | 2     | ✅        | ❌      | The domain account                       |
-    /// | 3     | ❌        | ❌      | The new owner of the domain name account |
-    /// | 4     | ✅        | ❌      | The lamports target                      |
-    /// | 5     | ✅        | ✅      | The authority of the registry            |
-    /// | 6     | ❌        | ❌      | The SPL name service program ID          |
+    /// | Index | Writable | Signer | Description                                 |
+    /// | ----------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account                  |
+    /// | 1     | ✅        | ❌      | The registrar account                       |
+    /// | 2     | ✅        | ❌      | The domain account                          |
+    /// | 3     | ❌        | ❌      | The new owner of the domain name account    |
+    /// | 4     | ✅        | ❌      | The lamports target                         |
+    /// | 5     | ✅        | ✅      | The authority of the registry               |
+    /// | 6     | ❌        | ❌      | The SPL name service program ID             |
+    /// | 7     | ❌        | ❌      | The SPL token program account               |
+    /// | 8     | ✅        | ❌      | The registrar vault, may not be initialized |
     CloseRegistrar,
-    /// Allow the authority of a `Registrar` to register a subdomain without token transfer
-    ///
-    /// | Index | Writable | Signer | Description                          |
-    /// | ---------------------------------------------------------------- |
-    /// | 0     | ❌        | ❌      | The system program account           |
-    /// | 1     | ❌        | ❌      | The SPL token program account        |
-    /// | 2     | ❌        | ❌      | The SPL name service program account |
-    /// | 3     | ❌        | ❌      | The rent sysvar account              |
-    /// | 4     | ❌        | ❌      | The sns registrar program account    |
-    /// | 5     | ❌        | ❌      | The .sol root domain                 |
-    /// | 6     | ❌        | ❌      | The reverse lookup class accoutn     |
-    /// | 7     | ✅        | ❌      | The registrar account                |
-    /// | 8     | ✅        | ❌      | The parent domain account            |
-    /// | 9     | ✅        | ❌      | The subdomain account to create      |
-    /// | 10    | ✅        | ❌      | The subdomain reverse account        |
-    /// | 11    | ✅        | ❌      | The subrecord account                |
-    /// | 12    | ✅        | ✅      | The fee payer account                |
+    /// Allow the authority of a `Registrar` or a delegate to register a subdomain without token transfer
+    ///
+    /// | Index | Writable | Signer | Description                                                               |
+    /// | ----------------------------------------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account                                                |
+    /// | 1     | ❌        | ❌      | The SPL token program account                                             |
+    /// | 2     | ❌        | ❌      | The SPL name service program account                                      |
+    /// | 3     | ❌        | ❌      | The rent sysvar account                                                   |
+    /// | 4     | ❌        | ❌      | The program creating the reverse lookups of the TLD root                  |
+    /// | 5     | ❌        | ❌      | The TLD root domain of the registrar                                      |
+    /// | 6     | ❌        | ❌      | The reverse lookup class of the TLD root                                  |
+    /// | 7     | ✅        | ❌      | The registrar account                                                     |
+    /// | 8     | ✅        | ❌      | The parent domain account                                                 |
+    /// | 9     | ✅        | ❌      | The subdomain account to create                                           |
+    /// | 10    | ✅        | ❌      | The subdomain reverse account                                             |
+    /// | 11    | ✅        | ❌      | The subrecord account                                                     |
+    /// | 12    | ✅        | ✅      | The registrar authority or a delegate with the `AdminRegister` permission |
+    /// | 13    | ❌        | ❌      | The delegate account if the signer is not the registrar authority         |
     AdminRegister,
     /// Delete a subrecord account account
     ///
@@ -106,19 +124,20 @@ pub enum ProgramInstruction {
     /// | 3     | ✅        | ❌      | The lamports target     |
     /// | 4     | ✅        | ❌      | The mint record account |
     DeleteSubdomainRecord,
-    /// Allow the authority of a `Registrar` to revoke a subdomain
+    /// Allow the authority of a `Registrar` or a delegate to revoke a subdomain
     ///
-    /// | Index | Writable | Signer | Description                     |
-    /// | ----------------------------------------------------------- |
-    /// | 0     | ✅        | ❌      | The registrar account           |
-    /// | 1     | ✅        | ❌      | The subdomain account to create |
-    /// | 2     | ✅        | ❌      | The subrecord account           |
-    /// | 3     | ❌        | ❌      | The current sub domain owner    |
-    /// | 4     | ❌        | ❌      | The parent domain               |
-    /// | 5     | ✅        | ✅      | The fee payer account           |
-    /// | 6     | ❌        | ❌      | Name class                      |
-    /// | 7     | ❌        | ❌      | The name service program ID     |
-    /// | 8     | ✅        | ❌      |                                 |
+    /// | Index | Writable | Signer | Description                                                        |
+    /// | ---------------------------------------------------------------------------------------------- |
+    /// | 0     | ✅        | ❌      | The registrar account                                              |
+    /// | 1     | ✅        | ❌      | The subdomain account to create                                    |
+    /// | 2     | ✅        | ❌      | The subrecord account                                              |
+    /// | 3     | ❌        | ❌      | The current sub domain owner                                       |
+    /// | 4     | ❌        | ❌      | The parent domain                                                  |
+    /// | 5     | ✅        | ✅      | The registrar authority or a delegate with the `Revoke` permission |
+    /// | 6     | ❌        | ❌      | Name class                                                         |
+    /// | 7     | ❌        | ❌      | The name service program ID                                        |
+    /// | 8     | ✅        | ❌      |                                                                    |
+    /// | 9     | ❌        | ❌      | The delegate account if the signer is not the registrar authority  |
     AdminRevoke,
     /// In the case of ...
     ///
@@ -127,7 +146,7 @@ pub enum ProgramInstruction {
     /// | 0     | ✅        | ❌      | The registrar account           |
     /// | 1     | ✅        | ❌      | The subdomain account to create |
     /// | 2     | ✅        | ❌      | The subrecord account           |
-    /// | 3     | ❌        | ❌      | The current sub domain owner    |
+    /// | 3     | ✅        | ❌      | The current sub domain owner    |
     /// | 4     | ❌        | ❌      | The parent domain               |
     /// | 5     | ✅        | ✅      | The fee payer account           |
     /// | 6     | ❌        | ❌      | The NFT account                 |
@@ -136,6 +155,205 @@ pub enum ProgramInstruction {
     /// | 9     | ❌        | ❌      | Name class                      |
     /// | 10    | ❌        | ❌      | The name service program ID     |
     NftOwnerRevoke,
+    /// Create the global config account
+    ///
+    /// | Index | Writable | Signer | Description                    |
+    /// | ---------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account     |
+    /// | 1     | ✅        | ❌      | The config account             |
+    /// | 2     | ❌        | ✅      | The default fee account owner  |
+    /// | 3     | ✅        | ✅      | The fee payer account          |
+    CreateConfig,
+    /// Edit the global config account
+    ///
+    /// | Index | Writable | Signer | Description                |
+    /// | ------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account |
+    /// | 1     | ✅        | ✅      | The admin of the config    |
+    /// | 2     | ✅        | ❌      | The config account         |
+    EditConfig,
+    /// Create a token vault owned by the registrar to collect the proceeds
+    ///
+    /// | Index | Writable | Signer | Description                       |
+    /// | ------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account        |
+    /// | 1     | ❌        | ❌      | The SPL token program account     |
+    /// | 2     | ❌        | ❌      | The rent sysvar account           |
+    /// | 3     | ❌        | ❌      | The registrar account             |
+    /// | 4     | ✅        | ❌      | The vault token account to create |
+    /// | 5     | ❌        | ❌      | The mint of the registrar         |
+    /// | 6     | ✅        | ✅      | The authority of the registrar    |
+    CreateVault,
+    /// Allow the authority of a `Registrar` to withdraw funds from the vault
+    ///
+    /// | Index | Writable | Signer | Description                    |
+    /// | ---------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The SPL token program account  |
+    /// | 1     | ❌        | ❌      | The registrar account          |
+    /// | 2     | ✅        | ❌      | The vault token account        |
+    /// | 3     | ✅        | ❌      | The destination token account  |
+    /// | 4     | ❌        | ✅      | The authority of the registrar |
+    Withdraw,
+    /// Quote the registration price of a subdomain
+    ///
+    /// | Index | Writable | Signer | Description                                             |
+    /// | ----------------------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The registrar account                                   |
+    /// | 1     | ❌        | ❌      | The global config account, may not be initialized       |
+    /// | 2     | ❌        | ❌      | The subdomain account to quote                          |
+    /// | 3     | ❌        | ❌      | The subrecord account                                   |
+    /// | 4     | ❌        | ❌      | The buyer of the subdomain                              |
+    /// | 5     | ❌        | ❌      | Optional NFT account if Registrar is NFT gated          |
+    /// | 6     | ❌        | ❌      | Optional NFT metadata account if Registrar is NFT gated |
+    /// | 7     | ❌        | ❌      | Optional NFT mint record if Registrar is NFT gated      |
+    QuotePrice,
+    /// Allow the authority of a `Registrar` or a delegate to register several subdomains without token transfer
+    ///
+    /// | Index | Writable | Signer | Description                                                               |
+    /// | ----------------------------------------------------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account                                                |
+    /// | 1     | ❌        | ❌      | The SPL token program account                                             |
+    /// | 2     | ❌        | ❌      | The SPL name service program account                                      |
+    /// | 3     | ❌        | ❌      | The rent sysvar account                                                   |
+    /// | 4     | ❌        | ❌      | The program creating the reverse lookups of the TLD root                  |
+    /// | 5     | ❌        | ❌      | The TLD root domain of the registrar                                      |
+    /// | 6     | ❌        | ❌      | The reverse lookup class of the TLD root                                  |
+    /// | 7     | ✅        | ❌      | The registrar account                                                     |
+    /// | 8     | ✅        | ❌      | The parent domain account                                                 |
+    /// | 9     | ✅        | ✅      | The registrar authority or a delegate with the `AdminRegister` permission |
+    /// | 10    | ❌        | ❌      | The delegate account if the signer is not the registrar authority         |
+    /// | 11    | ✅        | ❌      | The subdomain, subdomain reverse and subrecord accounts of each subdomain |
+    AdminRegisterBatch,
+    /// Allow anyone to revoke a subdomain once its expiry timestamp is reached
+    /// Soulbound subdomains can also be revoked by anyone once they are transferred
+    ///
+    /// | Index | Writable | Signer | Description                                                         |
+    /// | ----------------------------------------------------------------------------------------------- |
+    /// | 0     | ✅        | ❌      | The registrar account                                               |
+    /// | 1     | ✅        | ❌      | The subdomain account to revoke                                     |
+    /// | 2     | ✅        | ❌      | The subrecord account                                               |
+    /// | 3     | ✅        | ❌      | The current sub domain owner                                        |
+    /// | 4     | ❌        | ❌      | The parent domain                                                   |
+    /// | 5     | ❌        | ❌      | Name class                                                          |
+    /// | 6     | ❌        | ❌      | The name service program ID                                         |
+    /// | 7     | ✅        | ❌      | The mint record account if the subdomain was registered with an NFT |
+    RevokeExpired,
+    /// Allow the authority of a `Registrar` or a delegate to revoke several subdomains
+    ///
+    /// | Index | Writable | Signer | Description                                                                                 |
+    /// | ----------------------------------------------------------------------------------------------------------------------- |
+    /// | 0     | ✅        | ❌      | The registrar account                                                                       |
+    /// | 1     | ❌        | ❌      | The parent domain                                                                           |
+    /// | 2     | ✅        | ✅      | The registrar authority or a delegate with the `Revoke` permission                          |
+    /// | 3     | ❌        | ❌      | Name class                                                                                  |
+    /// | 4     | ❌        | ❌      | The name service program ID                                                                 |
+    /// | 5     | ❌        | ❌      | The delegate account if the signer is not the registrar authority                           |
+    /// | 6     | ✅        | ❌      | The subdomain, subrecord, current owner and optional mint record accounts of each subdomain |
+    AdminRevokeBatch,
+    /// Record the current owner of a subdomain in its subrecord
+    ///
+    /// | Index | Writable | Signer | Description           |
+    /// | ------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The subdomain account |
+    /// | 1     | ✅        | ❌      | The subrecord account |
+    SyncOwner,
+    /// Allow the authority of a `Registrar` or a delegate to transfer a subdomain to a new owner
+    ///
+    /// | Index | Writable | Signer | Description                                                          |
+    /// | ------------------------------------------------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The registrar account                                                |
+    /// | 1     | ✅        | ❌      | The subdomain account to transfer                                    |
+    /// | 2     | ✅        | ❌      | The subrecord account                                                |
+    /// | 3     | ❌        | ❌      | The parent domain                                                    |
+    /// | 4     | ❌        | ✅      | The registrar authority or a delegate with the `Transfer` permission |
+    /// | 5     | ❌        | ❌      | Name class                                                           |
+    /// | 6     | ❌        | ❌      | The name service program ID                                          |
+    /// | 7     | ❌        | ❌      | The delegate account if the signer is not the registrar authority    |
+    AdminTransfer,
+    /// Propose a new authority for a `Registrar`
+    ///
+    /// | Index | Writable | Signer | Description                            |
+    /// | ------------------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account             |
+    /// | 1     | ✅        | ✅      | The current authority of the registrar |
+    /// | 2     | ✅        | ❌      | The registrar account                  |
+    ProposeAuthority,
+    /// Accept the authority of a `Registrar` proposed with `ProposeAuthority`
+    ///
+    /// | Index | Writable | Signer | Description                            |
+    /// | ------------------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account             |
+    /// | 1     | ✅        | ✅      | The pending authority of the registrar |
+    /// | 2     | ✅        | ❌      | The registrar account                  |
+    AcceptAuthority,
+    /// Cancel the authority transfer of a `Registrar` proposed with `ProposeAuthority`
+    ///
+    /// | Index | Writable | Signer | Description                            |
+    /// | ------------------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account             |
+    /// | 1     | ✅        | ✅      | The current authority of the registrar |
+    /// | 2     | ✅        | ❌      | The registrar account                  |
+    CancelAuthorityTransfer,
+    /// Allow the authority of a `Registrar` to grant, update or remove the permissions of a delegate
+    ///
+    /// | Index | Writable | Signer | Description                |
+    /// | ------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account |
+    /// | 1     | ❌        | ❌      | The registrar account      |
+    /// | 2     | ✅        | ✅      | The registrar authority    |
+    /// | 3     | ✅        | ❌      | The delegate account       |
+    SetDelegate,
+    /// Apply a timelocked registrar edit once its delay has elapsed
+    ///
+    /// | Index | Writable | Signer | Description                     |
+    /// | ----------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The system program account      |
+    /// | 1     | ✅        | ❌      | The registrar account           |
+    /// | 2     | ✅        | ❌      | The pending change account      |
+    /// | 3     | ✅        | ❌      | The payer of the pending change |
+    /// | 4     | ✅        | ✅      | The fee payer account           |
+    ExecuteChange,
+    /// Cancel a timelocked registrar edit
+    ///
+    /// | Index | Writable | Signer | Description                     |
+    /// | ----------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The registrar account           |
+    /// | 1     | ❌        | ✅      | The registrar authority         |
+    /// | 2     | ✅        | ❌      | The pending change account      |
+    /// | 3     | ✅        | ❌      | The payer of the pending change |
+    CancelChange,
+    /// Make a subdomain irrevocable
+    ///
+    /// | Index | Writable | Signer | Description                  |
+    /// | -------------------------------------------------------- |
+    /// | 0     | ❌        | ❌      | The registrar account        |
+    /// | 1     | ✅        | ❌      | The subdomain record account |
+    /// | 2     | ❌        | ✅      | The registrar authority      |
+    SetIrrevocable,
+    /// Migrate an account using an outdated layout to the current layout
+    ///
+    /// | Index | Writable | Signer | Description                |
+    /// | ------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account |
+    /// | 1     | ✅        | ❌      | The account to migrate     |
+    /// | 2     | ✅        | ✅      | The fee payer account      |
+    Migrate,
+    /// Stop the registrations of a registrar ahead of closing it
+    ///
+    /// | Index | Writable | Signer | Description                |
+    /// | ------------------------------------------------------ |
+    /// | 0     | ❌        | ❌      | The system program account |
+    /// | 1     | ✅        | ✅      | The registrar authority    |
+    /// | 2     | ✅        | ❌      | The registrar account      |
+    WindDown,
+    /// Move the accounting of a subdomain registered with a previous registrar at the same address
+    ///
+    /// | Index | Writable | Signer | Description                  |
+    /// | -------------------------------------------------------- |
+    /// | 0     | ✅        | ❌      | The registrar account        |
+    /// | 1     | ✅        | ❌      | The subdomain record account |
+    /// | 2     | ❌        | ✅      | The registrar authority      |
+    MigrateRegistrar,
 }
 pub fn create_registrar(
     accounts: create_registrar::Accounts<Pubkey>,
@@ -192,3 +410,134 @@ pub fn nft_owner_revoke(
 ) -> Instruction {
     accounts.get_instruction(crate::ID, ProgramInstruction::NftOwnerRevoke as u8, params)
 }
+pub fn create_config(
+    accounts: create_config::Accounts<Pubkey>,
+    params: create_config::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::CreateConfig as u8, params)
+}
+pub fn edit_config(
+    accounts: edit_config::Accounts<Pubkey>,
+    params: edit_config::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::EditConfig as u8, params)
+}
+pub fn create_vault(
+    accounts: create_vault::Accounts<Pubkey>,
+    params: create_vault::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::CreateVault as u8, params)
+}
+pub fn withdraw(accounts: withdraw::Accounts<Pubkey>, params: withdraw::Params) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::Withdraw as u8, params)
+}
+pub fn quote_price(
+    accounts: quote_price::Accounts<Pubkey>,
+    params: quote_price::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::QuotePrice as u8, params)
+}
+pub fn admin_register_batch(
+    accounts: admin_register_batch::Accounts<Pubkey>,
+    params: admin_register_batch::Params,
+) -> Instruction {
+    accounts.get_instruction(
+        crate::ID,
+        ProgramInstruction::AdminRegisterBatch as u8,
+        params,
+    )
+}
+pub fn revoke_expired(
+    accounts: revoke_expired::Accounts<Pubkey>,
+    params: revoke_expired::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::RevokeExpired as u8, params)
+}
+pub fn admin_revoke_batch(
+    accounts: admin_revoke_batch::Accounts<Pubkey>,
+    params: admin_revoke_batch::Params,
+) -> Instruction {
+    accounts.get_instruction(
+        crate::ID,
+        ProgramInstruction::AdminRevokeBatch as u8,
+        params,
+    )
+}
+pub fn sync_owner(
+    accounts: sync_owner::Accounts<Pubkey>,
+    params: sync_owner::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::SyncOwner as u8, params)
+}
+pub fn admin_transfer(
+    accounts: admin_transfer::Accounts<Pubkey>,
+    params: admin_transfer::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::AdminTransfer as u8, params)
+}
+pub fn propose_authority(
+    accounts: propose_authority::Accounts<Pubkey>,
+    params: propose_authority::Params,
+) -> Instruction {
+    accounts.get_instruction(
+        crate::ID,
+        ProgramInstruction::ProposeAuthority as u8,
+        params,
+    )
+}
+pub fn accept_authority(
+    accounts: accept_authority::Accounts<Pubkey>,
+    params: accept_authority::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::AcceptAuthority as u8, params)
+}
+pub fn cancel_authority_transfer(
+    accounts: cancel_authority_transfer::Accounts<Pubkey>,
+    params: cancel_authority_transfer::Params,
+) -> Instruction {
+    accounts.get_instruction(
+        crate::ID,
+        ProgramInstruction::CancelAuthorityTransfer as u8,
+        params,
+    )
+}
+pub fn set_delegate(
+    accounts: set_delegate::Accounts<Pubkey>,
+    params: set_delegate::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::SetDelegate as u8, params)
+}
+pub fn execute_change(
+    accounts: execute_change::Accounts<Pubkey>,
+    params: execute_change::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::ExecuteChange as u8, params)
+}
+pub fn cancel_change(
+    accounts: cancel_change::Accounts<Pubkey>,
+    params: cancel_change::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::CancelChange as u8, params)
+}
+pub fn set_irrevocable(
+    accounts: set_irrevocable::Accounts<Pubkey>,
+    params: set_irrevocable::Params,
+) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::SetIrrevocable as u8, params)
+}
+pub fn migrate(accounts: migrate::Accounts<Pubkey>, params: migrate::Params) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::Migrate as u8, params)
+}
+pub fn wind_down(accounts: wind_down::Accounts<Pubkey>, params: wind_down::Params) -> Instruction {
+    accounts.get_instruction(crate::ID, ProgramInstruction::WindDown as u8, params)
+}
+pub fn migrate_registrar(
+    accounts: migrate_registrar::Accounts<Pubkey>,
+    params: migrate_registrar::Params,
+) -> Instruction {
+    accounts.get_instruction(
+        crate::ID,
+        ProgramInstruction::MigrateRegistrar as u8,
+        params,
+    )
+}
@@ -9,15 +9,35 @@ use {
 
 use crate::instruction::ProgramInstruction;
 
+pub mod accept_authority;
 pub mod admin_register;
+pub mod admin_register_batch;
 pub mod admin_revoke;
+pub mod admin_revoke_batch;
+pub mod admin_transfer;
+pub mod cancel_authority_transfer;
+pub mod cancel_change;
 pub mod close_registrar;
+pub mod create_config;
 pub mod create_registrar;
+pub mod create_vault;
 pub mod delete_subdomain_record;
+pub mod edit_config;
 pub mod edit_registrar;
+pub mod execute_change;
+pub mod migrate;
+pub mod migrate_registrar;
 pub mod nft_owner_revoke;
+pub mod propose_authority;
+pub mod quote_price;
 pub mod register;
+pub mod revoke_expired;
+pub mod set_delegate;
+pub mod set_irrevocable;
+pub mod sync_owner;
 pub mod unregister;
+pub mod wind_down;
+pub mod withdraw;
 
 pub struct Processor {}
 
@@ -88,6 +108,126 @@ impl Processor {
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
                 nft_owner_revoke::process(program_id, accounts, params)?;
             }
+            ProgramInstruction::CreateConfig => {
+                msg!("[+] Instruction: Create config instruction");
+                let params = create_config::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                create_config::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::EditConfig => {
+                msg!("[+] Instruction: Edit config instruction");
+                let params = edit_config::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                edit_config::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::CreateVault => {
+                msg!("[+] Instruction: Create vault instruction");
+                let params = create_vault::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                create_vault::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::Withdraw => {
+                msg!("[+] Instruction: Withdraw instruction");
+                let params = withdraw::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                withdraw::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::QuotePrice => {
+                msg!("[+] Instruction: Quote price");
+                let params = quote_price::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                quote_price::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::AdminRegisterBatch => {
+                msg!("[+] Instruction: Admin register batch");
+                let params = admin_register_batch::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                admin_register_batch::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::RevokeExpired => {
+                msg!("[+] Instruction: Revoke expired");
+                let params = revoke_expired::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                revoke_expired::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::AdminRevokeBatch => {
+                msg!("[+] Instruction: Admin revoke batch");
+                let params = admin_revoke_batch::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                admin_revoke_batch::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::SyncOwner => {
+                msg!("[+] Instruction: Sync owner");
+                let params = sync_owner::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                sync_owner::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::AdminTransfer => {
+                msg!("[+] Instruction: Admin transfer");
+                let params = admin_transfer::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                admin_transfer::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::ProposeAuthority => {
+                msg!("[+] Instruction: Propose authority");
+                let params = propose_authority::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                propose_authority::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::AcceptAuthority => {
+                msg!("[+] Instruction: Accept authority");
+                let params = accept_authority::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                accept_authority::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::CancelAuthorityTransfer => {
+                msg!("[+] Instruction: Cancel authority transfer");
+                let params = cancel_authority_transfer::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                cancel_authority_transfer::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::SetDelegate => {
+                msg!("[+] Instruction: Set delegate");
+                let params = set_delegate::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                set_delegate::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::ExecuteChange => {
+                msg!("[+] Instruction: Execute change Instruction");
+                let params = execute_change::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                execute_change::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::CancelChange => {
+                msg!("[+] Instruction: Cancel change Instruction");
+                let params = cancel_change::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                cancel_change::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::SetIrrevocable => {
+                msg!("[+] Instruction: Set irrevocable Instruction");
+                let params = set_irrevocable::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                set_irrevocable::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::Migrate => {
+                msg!("[+] Instruction: Migrate Instruction");
+                let params = migrate::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                migrate::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::WindDown => {
+                msg!("[+] Instruction: Wind down Instruction");
+                let params = wind_down::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                wind_down::process(program_id, accounts, params)?;
+            }
+            ProgramInstruction::MigrateRegistrar => {
+                msg!("[+] Instruction: Migrate registrar Instruction");
+                let params = migrate_registrar::Params::try_from_slice(instruction_data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                migrate_registrar::process(program_id, accounts, params)?;
+            }
         }
 
         Ok(())
@@ -1,9 +1,13 @@
-//! Allow the authority of a `Registrar` to register a subdomain without token transfer
+//! Allow the authority of a `Registrar` or a delegate to register a subdomain without token
+//! transfer
 use crate::{
     cpi::Cpi,
     error::SubRegisterError,
-    state::{registry::Registrar, subdomain_record::SubDomainRecord, Tag, ROOT_DOMAIN_ACCOUNT},
-    utils::get_subdomain_reverse,
+    state::{delegate::Permission, registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+    utils::{
+        check_authority_or_delegate, check_subdomain_name, find_account,
+        get_subdomain_reverse_with_class, next_account_with_tag,
+    },
 };
 use sns_registrar::processor::create_reverse;
 
@@ -16,6 +20,7 @@ use {
     sns_registrar::instruction_auto::create_reverse,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
         hash::hashv,
         msg,
@@ -34,6 +39,14 @@ use {
 pub struct Params {
     /// The subdomain to register
     pub domain: String,
+    /// The owner of the subdomain, defaults to the authority
+    /// The recipient account must be appended to the accounts if it is not the authority
+    pub recipient: Option<Pubkey>,
+    /// Optional timestamp from which anyone can revoke the subdomain
+    pub expiry_timestamp: Option<i64>,
+    /// Restricts the `allow_revoke` setting of the registrar for this subdomain, `Some(true)` is
+    /// rejected if the registrar does not allow revocation
+    pub allow_revoke: Option<bool>,
 }
 
 #[derive(InstructionsAccount)]
@@ -50,13 +63,13 @@ pub struct Accounts<'a, T> {
     /// The rent sysvar account
     pub rent_sysvar: &'a T,
 
-    /// The sns registrar program account
+    /// The program creating the reverse lookups of the TLD root
     pub sns_registrar_program: &'a T,
 
-    /// The .sol root domain
+    /// The TLD root domain of the registrar
     pub root_domain: &'a T,
 
-    /// The reverse lookup class accoutn
+    /// The reverse lookup class of the TLD root
     pub reverse_lookup_class: &'a T,
 
     /// The registrar account
@@ -80,8 +93,11 @@ pub struct Accounts<'a, T> {
     pub sub_record: &'a T,
 
     #[cons(writable, signer)]
-    /// The fee payer account
+    /// The registrar authority or a delegate with the `AdminRegister` permission
     pub authority: &'a T,
+
+    /// The delegate account if the signer is not the registrar authority
+    pub delegate: Option<&'a T>,
 }
 
 impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
@@ -104,6 +120,7 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             sub_reverse_account: next_account_info(accounts_iter)?,
             sub_record: next_account_info(accounts_iter)?,
             authority: next_account_info(accounts_iter)?,
+            delegate: next_account_with_tag(accounts_iter, program_id, Tag::Delegate),
         };
 
         // Check keys
@@ -111,21 +128,10 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
         check_account_key(accounts.spl_token_program, &spl_token::ID)?;
         check_account_key(accounts.spl_name_service, &spl_name_service::ID)?;
         check_account_key(accounts.rent_sysvar, &sysvar::rent::id())?;
-        check_account_key(accounts.sns_registrar_program, &sns_registrar::ID)?;
-        check_account_key(accounts.root_domain, &ROOT_DOMAIN_ACCOUNT)?;
-        check_account_key(
-            accounts.reverse_lookup_class,
-            &sns_registrar::central_state::KEY,
-        )?;
 
         // Check owners
         check_account_owner(accounts.registrar, program_id)?;
         check_account_owner(accounts.parent_domain_account, &spl_name_service::ID)?;
-        check_account_owner(accounts.sub_domain_account, &system_program::ID)?;
-        check_account_owner(accounts.sub_reverse_account, &system_program::ID).or_else(|_| {
-            check_account_owner(accounts.sub_reverse_account, &spl_name_service::ID)
-        })?;
-        check_account_owner(accounts.sub_record, &system_program::ID)?;
 
         // Check signer
         check_signer(accounts.authority)?;
@@ -135,35 +141,101 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
 }
 
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let account_infos = accounts;
     let accounts = Accounts::parse(accounts, program_id)?;
-    let (subrecord_key, subrecord_nonce) =
-        SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
     let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
 
-    check_account_key(accounts.authority, &registrar.authority)?;
+    check_authority_or_delegate(
+        program_id,
+        accounts.registrar.key,
+        &registrar,
+        accounts.authority,
+        accounts.delegate,
+        Permission::AdminRegister,
+    )?;
     check_account_key(accounts.parent_domain_account, &registrar.domain_account)?;
-    check_account_key(accounts.sub_record, &subrecord_key)?;
 
-    let sub_key = get_subdomain_reverse(
-        params.domain.strip_prefix('\x00').unwrap(),
-        accounts.parent_domain_account.key,
-    );
-    check_account_key(accounts.sub_reverse_account, &sub_key)?;
+    create_subdomain(
+        program_id,
+        &accounts,
+        &registrar,
+        params.domain,
+        find_account(
+            account_infos,
+            &params.recipient.unwrap_or(*accounts.authority.key),
+        )?,
+        params.expiry_timestamp,
+        params.allow_revoke,
+    )?;
+
+    // Increment nb sub created
+    registrar.total_sub_created = registrar
+        .total_sub_created
+        .checked_add(1)
+        .ok_or(SubRegisterError::Overflow)?;
+
+    // Serialize state
+    registrar.save(&mut accounts.registrar.data.borrow_mut());
+
+    Ok(())
+}
 
-    if !params.domain.starts_with('\0') {
-        return Err(SubRegisterError::InvalidSubdomain.into());
+// Creates the subdomain, its reverse and its subrecord, the subdomain is owned by `owner`
+// The registrar authority and parent domain must be checked by the caller, the accounts of the
+// TLD root are checked against the registrar
+// `total_sub_created` is not updated so that batches serialize the registrar once
+pub(crate) fn create_subdomain<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &Accounts<'a, AccountInfo<'b>>,
+    registrar: &Registrar,
+    domain: String,
+    owner: &'a AccountInfo<'b>,
+    expiry_timestamp: Option<i64>,
+    allow_revoke: Option<bool>,
+) -> ProgramResult {
+    if registrar.is_winding_down() {
+        return Err(SubRegisterError::RegistrarWindingDown.into());
     }
 
-    if params.domain.trim().to_lowercase() != params.domain {
-        return Err(SubRegisterError::InvalidSubdomain.into());
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    if expiry_timestamp
+        .map(|t| t <= current_timestamp)
+        .unwrap_or(false)
+    {
+        msg!("The expiry timestamp must be in the future");
+        return Err(ProgramError::InvalidArgument);
     }
 
-    if params.domain.contains('.') {
-        return Err(SubRegisterError::InvalidSubdomain.into());
+    if allow_revoke == Some(true) && !registrar.allow_revoke {
+        msg!("The registrar does not allow revocation");
+        return Err(ProgramError::InvalidArgument);
     }
 
+    check_account_owner(accounts.sub_domain_account, &system_program::ID)?;
+    check_account_owner(accounts.sub_reverse_account, &system_program::ID)
+        .or_else(|_| check_account_owner(accounts.sub_reverse_account, &spl_name_service::ID))?;
+    check_account_owner(accounts.sub_record, &system_program::ID)?;
+
+    let (subrecord_key, subrecord_nonce) =
+        SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
+    check_account_key(accounts.sub_record, &subrecord_key)?;
+
+    check_subdomain_name(&domain)?;
+
+    registrar.root_domain.check_accounts(
+        accounts.root_domain,
+        accounts.sns_registrar_program,
+        accounts.reverse_lookup_class,
+    )?;
+    let sub_key = get_subdomain_reverse_with_class(
+        domain.strip_prefix('\x00').unwrap(),
+        accounts.parent_domain_account.key,
+        &registrar.root_domain.reverse_lookup_class,
+    );
+    check_account_key(accounts.sub_reverse_account, &sub_key)?;
+
     // Check sub account derivation
-    let hashed_name = hashv(&[(HASH_PREFIX.to_owned() + &params.domain).as_bytes()])
+    let hashed_name = hashv(&[(HASH_PREFIX.to_owned() + &domain).as_bytes()])
         .as_ref()
         .to_vec();
     if hashed_name.len() != 32 {
@@ -191,7 +263,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
         },
         *accounts.sub_domain_account.key,
         *accounts.authority.key,
-        *accounts.authority.key,
+        *owner.key,
         None,
         Some(registrar.domain_account),
         Some(*accounts.registrar.key),
@@ -209,7 +281,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
             accounts.system_program.clone(),
             accounts.authority.clone(),
             accounts.sub_domain_account.clone(),
-            accounts.authority.clone(),
+            owner.clone(),
             accounts.parent_domain_account.clone(),
             accounts.registrar.clone(),
         ],
@@ -219,21 +291,19 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     // Sub reverse should be passed in the accounts and check if does not already exist
     if accounts.sub_reverse_account.data_is_empty() {
         let ix = create_reverse(
-            sns_registrar::ID,
+            registrar.root_domain.registrar_program,
             create_reverse::Accounts {
                 naming_service_program: &spl_name_service::ID,
-                root_domain: &ROOT_DOMAIN_ACCOUNT,
+                root_domain: &registrar.root_domain.name_account,
                 reverse_lookup: accounts.sub_reverse_account.key,
                 system_program: &system_program::ID,
-                central_state: &sns_registrar::central_state::KEY,
+                central_state: &registrar.root_domain.reverse_lookup_class,
                 fee_payer: accounts.authority.key,
                 rent_sysvar: accounts.rent_sysvar.key,
                 parent_name: Some(accounts.parent_domain_account.key),
                 parent_name_owner: Some(accounts.registrar.key),
             },
-            create_reverse::Params {
-                name: params.domain,
-            },
+            create_reverse::Params { name: domain },
         );
         invoke_signed(
             &ix,
@@ -255,11 +325,17 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     }
 
     // Create subrecord account
-    let sub_record = SubDomainRecord::new(
+    let mut sub_record = SubDomainRecord::new(
         *accounts.registrar.key,
         *accounts.sub_domain_account.key,
         *accounts.authority.key,
     );
+    sub_record.registered_at = current_timestamp;
+    sub_record.expiry_timestamp = expiry_timestamp.unwrap_or(i64::MAX);
+    sub_record.allow_revoke = allow_revoke;
+    sub_record.bound_owner = registrar.soulbound.then_some(*owner.key);
+    sub_record.owner = *owner.key;
+    sub_record.last_owner_sync = current_timestamp;
     let seeds: &[&[u8]] = &[
         SubDomainRecord::SEEDS,
         &accounts.sub_domain_account.key.to_bytes(),
@@ -275,14 +351,5 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     )?;
     sub_record.save(&mut accounts.sub_record.data.borrow_mut());
 
-    // Increment nb sub created
-    registrar.total_sub_created = registrar
-        .total_sub_created
-        .checked_add(1)
-        .ok_or(SubRegisterError::Overflow)?;
-
-    // Serialize state
-    registrar.save(&mut accounts.registrar.data.borrow_mut());
-
     Ok(())
 }
@@ -0,0 +1,205 @@
+//! Allow the authority of a `Registrar` or a delegate to register several subdomains without token
+//! transfer
+
+use crate::{
+    error::SubRegisterError,
+    processor::admin_register::{self, create_subdomain},
+    state::{delegate::Permission, registry::Registrar, Tag},
+    utils::{check_authority_or_delegate, find_account, next_account_with_tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program, sysvar,
+    },
+};
+
+/// The maximum number of subdomains registered in a single instruction
+/// Each subdomain costs 3 accounts and its parameters, a legacy transaction with a compute budget
+/// instruction fits 5 subdomains of 10 characters (see `tests/batch.rs`)
+pub const MAX_BATCH_SIZE: usize = 5;
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Item {
+    /// The subdomain to register
+    pub domain: String,
+    /// The owner of the subdomain, defaults to the authority
+    /// The recipient account must be appended after the subdomain accounts
+    pub recipient: Option<Pubkey>,
+    /// Optional timestamp from which anyone can revoke the subdomain
+    pub expiry_timestamp: Option<i64>,
+    /// Restricts the `allow_revoke` setting of the registrar for this subdomain, `Some(true)` is
+    /// rejected if the registrar does not allow revocation
+    pub allow_revoke: Option<bool>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The subdomains to register, in the same order as the subdomain accounts
+    pub items: Vec<Item>,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    /// The SPL token program account
+    pub spl_token_program: &'a T,
+
+    /// The SPL name service program account
+    pub spl_name_service: &'a T,
+
+    /// The rent sysvar account
+    pub rent_sysvar: &'a T,
+
+    /// The program creating the reverse lookups of the TLD root
+    pub sns_registrar_program: &'a T,
+
+    /// The TLD root domain of the registrar
+    pub root_domain: &'a T,
+
+    /// The reverse lookup class of the TLD root
+    pub reverse_lookup_class: &'a T,
+
+    /// The registrar account
+    #[cons(writable)]
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The parent domain account
+    pub parent_domain_account: &'a T,
+
+    #[cons(writable, signer)]
+    /// The registrar authority or a delegate with the `AdminRegister` permission
+    pub authority: &'a T,
+
+    /// The delegate account if the signer is not the registrar authority
+    pub delegate: Option<&'a T>,
+
+    #[cons(writable)]
+    /// The subdomain, subdomain reverse and subrecord accounts of each subdomain, followed by the
+    /// recipient accounts
+    pub sub_accounts: &'a [T],
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            spl_token_program: next_account_info(accounts_iter)?,
+            spl_name_service: next_account_info(accounts_iter)?,
+            rent_sysvar: next_account_info(accounts_iter)?,
+            sns_registrar_program: next_account_info(accounts_iter)?,
+            root_domain: next_account_info(accounts_iter)?,
+            reverse_lookup_class: next_account_info(accounts_iter)?,
+            registrar: next_account_info(accounts_iter)?,
+            parent_domain_account: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            delegate: next_account_with_tag(accounts_iter, program_id, Tag::Delegate),
+            sub_accounts: accounts_iter.as_slice(),
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+        check_account_key(accounts.spl_token_program, &spl_token::ID)?;
+        check_account_key(accounts.spl_name_service, &spl_name_service::ID)?;
+        check_account_key(accounts.rent_sysvar, &sysvar::rent::id())?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.parent_domain_account, &spl_name_service::ID)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let account_infos = accounts;
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    check_authority_or_delegate(
+        program_id,
+        accounts.registrar.key,
+        &registrar,
+        accounts.authority,
+        accounts.delegate,
+        Permission::AdminRegister,
+    )?;
+    check_account_key(accounts.parent_domain_account, &registrar.domain_account)?;
+
+    if params.items.is_empty() || params.items.len() > MAX_BATCH_SIZE {
+        msg!(
+            "The batch must contain between 1 and {} subdomains",
+            MAX_BATCH_SIZE
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    let nb_sub_accounts = 3 * params.items.len();
+    if accounts.sub_accounts.len() < nb_sub_accounts {
+        return Err(SubRegisterError::MissingAccount.into());
+    }
+
+    for (item, sub_accounts) in params
+        .items
+        .into_iter()
+        .zip(accounts.sub_accounts[..nb_sub_accounts].chunks(3))
+    {
+        let item_accounts = admin_register::Accounts {
+            system_program: accounts.system_program,
+            spl_token_program: accounts.spl_token_program,
+            spl_name_service: accounts.spl_name_service,
+            rent_sysvar: accounts.rent_sysvar,
+            sns_registrar_program: accounts.sns_registrar_program,
+            root_domain: accounts.root_domain,
+            reverse_lookup_class: accounts.reverse_lookup_class,
+            registrar: accounts.registrar,
+            parent_domain_account: accounts.parent_domain_account,
+            sub_domain_account: &sub_accounts[0],
+            sub_reverse_account: &sub_accounts[1],
+            sub_record: &sub_accounts[2],
+            authority: accounts.authority,
+            delegate: accounts.delegate,
+        };
+        create_subdomain(
+            program_id,
+            &item_accounts,
+            &registrar,
+            item.domain,
+            find_account(
+                account_infos,
+                &item.recipient.unwrap_or(*accounts.authority.key),
+            )?,
+            item.expiry_timestamp,
+            item.allow_revoke,
+        )?;
+
+        registrar.total_sub_created = registrar
+            .total_sub_created
+            .checked_add(1)
+            .ok_or(SubRegisterError::Overflow)?;
+    }
+
+    // Serialize state
+    registrar.save(&mut accounts.registrar.data.borrow_mut());
+
+    Ok(())
+}
@@ -1,9 +1,13 @@
-//! Allow the authority of a `Registrar` to revoke a subdomain
+//! Allow the authority of a `Registrar` or a delegate to revoke a subdomain
 
 use crate::{
     error::SubRegisterError,
     revoke_unchecked,
-    state::{mint_record::MintRecord, registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+    state::{
+        delegate::Permission, mint_record::MintRecord, registry::Registrar,
+        subdomain_record::SubDomainRecord, Tag,
+    },
+    utils::{check_authority_or_delegate, next_account_with_tag},
 };
 
 use {
@@ -47,7 +51,7 @@ pub struct Accounts<'a, T> {
     pub parent_domain: &'a T,
 
     #[cons(writable, signer)]
-    /// The fee payer account
+    /// The registrar authority or a delegate with the `Revoke` permission
     pub authority: &'a T,
 
     /// Name class
@@ -58,6 +62,9 @@ pub struct Accounts<'a, T> {
 
     #[cons(writable)]
     pub mint_record: Option<&'a T>,
+
+    /// The delegate account if the signer is not the registrar authority
+    pub delegate: Option<&'a T>,
 }
 
 impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
@@ -75,7 +82,8 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             authority: next_account_info(accounts_iter)?,
             name_class: next_account_info(accounts_iter)?,
             spl_name_service: next_account_info(accounts_iter)?,
-            mint_record: next_account_info(accounts_iter).ok(),
+            mint_record: next_account_with_tag(accounts_iter, program_id, Tag::MintRecord),
+            delegate: next_account_with_tag(accounts_iter, program_id, Tag::Delegate),
         };
 
         // Check keys
@@ -101,28 +109,44 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
     let sub_record = SubDomainRecord::from_account_info(accounts.sub_record, Tag::SubRecord)?;
     let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
 
+    if sub_record.is_stale(&registrar) {
+        return Err(SubRegisterError::StaleSubRecord.into());
+    }
+
     let (subrecord_key, _) = SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
 
-    check_account_key(accounts.authority, &registrar.authority)?;
+    check_authority_or_delegate(
+        program_id,
+        accounts.registrar.key,
+        &registrar,
+        accounts.authority,
+        accounts.delegate,
+        Permission::Revoke,
+    )?;
     check_account_key(accounts.sub_record, &subrecord_key)?;
     check_account_key(accounts.parent_domain, &registrar.domain_account)?;
 
     let header = NameRecordHeader::unpack_from_slice(&accounts.sub_domain_account.data.borrow())?;
     check_account_key(accounts.sub_owner, &header.owner)?;
 
-    if !registrar.allow_revoke {
+    // Transferred soulbound subdomains can be revoked as long as the registrar allows revocation
+    if !registrar.allow_revoke
+        || sub_record.irrevocable
+        || (!sub_record.is_revocable(&registrar) && !sub_record.is_transferred(&header.owner))
+    {
         return Err(SubRegisterError::CannotRevoke.into());
     }
 
-    let (mr, mr_acc) = match (registrar.nft_gated_collection, accounts.mint_record) {
+    // Subdomains minted with an NFT keep their mint record even if the gated collection changed
+    let (mr, mr_acc) = match (sub_record.mint_record, accounts.mint_record) {
         (None, Some(_)) | (Some(_), None) => return Err(SubRegisterError::MissingMintRecord.into()),
         (None, None) => (None, None),
-        (Some(_), Some(mint_record_account)) => {
+        (Some(mint_record_key), Some(mint_record_account)) => {
             check_account_owner(mint_record_account, program_id)?;
 
             let mint_record = MintRecord::from_account_info(mint_record_account, Tag::MintRecord)?;
 
-            check_account_key(mint_record_account, &sub_record.mint_record.unwrap())?;
+            check_account_key(mint_record_account, &mint_record_key)?;
 
             (Some(mint_record), accounts.mint_record)
         }
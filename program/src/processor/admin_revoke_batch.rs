@@ -0,0 +1,207 @@
+//! Allow the authority of a `Registrar` or a delegate to revoke several subdomains
+//! Invalid items are skipped, the result of each item is written in the return data
+
+use crate::{
+    error::SubRegisterError,
+    revoke_unchecked,
+    state::{
+        delegate::Permission, mint_record::MintRecord, registry::Registrar,
+        subdomain_record::SubDomainRecord, Tag,
+    },
+    utils::{check_authority_or_delegate, next_account_with_tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::set_return_data,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+    },
+    spl_name_service::state::NameRecordHeader,
+};
+
+/// The result of an item when the subdomain was revoked
+/// Otherwise the result is the `u64` representation of the `ProgramError`
+pub const REVOKED: u64 = 0;
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    #[cons(writable)]
+    /// The registrar account
+    pub registrar: &'a T,
+
+    /// The parent domain
+    pub parent_domain: &'a T,
+
+    #[cons(writable, signer)]
+    /// The registrar authority or a delegate with the `Revoke` permission
+    pub authority: &'a T,
+
+    /// Name class
+    pub name_class: &'a T,
+
+    /// The name service program ID
+    pub spl_name_service: &'a T,
+
+    /// The delegate account if the signer is not the registrar authority
+    pub delegate: Option<&'a T>,
+
+    #[cons(writable)]
+    /// The subdomain, subrecord, current owner and optional mint record accounts of each subdomain
+    pub sub_accounts: &'a [T],
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            registrar: next_account_info(accounts_iter)?,
+            parent_domain: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            name_class: next_account_info(accounts_iter)?,
+            spl_name_service: next_account_info(accounts_iter)?,
+            delegate: next_account_with_tag(accounts_iter, program_id, Tag::Delegate),
+            sub_accounts: accounts_iter.as_slice(),
+        };
+
+        // Check keys
+        check_account_key(accounts.name_class, &Pubkey::default())?;
+        check_account_key(accounts.spl_name_service, &spl_name_service::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.parent_domain, &spl_name_service::ID)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+struct Item<'a, 'b> {
+    sub_domain_account: &'a AccountInfo<'b>,
+    sub_record: &'a AccountInfo<'b>,
+    sub_owner: &'a AccountInfo<'b>,
+    mint_record: Option<&'a AccountInfo<'b>>,
+}
+
+// Performs the checks of `admin_revoke` so that the CPIs of `revoke_unchecked` cannot fail
+fn check_item(
+    program_id: &Pubkey,
+    registrar: &Registrar,
+    item: &Item,
+) -> Result<(SubDomainRecord, Option<MintRecord>), ProgramError> {
+    check_account_owner(item.sub_domain_account, &spl_name_service::ID)?;
+    check_account_owner(item.sub_record, program_id)?;
+
+    let sub_record = SubDomainRecord::from_account_info(item.sub_record, Tag::SubRecord)?;
+    if sub_record.is_stale(registrar) {
+        return Err(SubRegisterError::StaleSubRecord.into());
+    }
+    let (subrecord_key, _) = SubDomainRecord::find_key(item.sub_domain_account.key, program_id);
+    check_account_key(item.sub_record, &subrecord_key)?;
+
+    let header = NameRecordHeader::unpack_from_slice(&item.sub_domain_account.data.borrow())?;
+    if header.parent_name != registrar.domain_account {
+        return Err(SubRegisterError::WrongNameAccount.into());
+    }
+    check_account_key(item.sub_owner, &header.owner)?;
+
+    if !registrar.allow_revoke
+        || sub_record.irrevocable
+        || (!sub_record.is_revocable(registrar) && !sub_record.is_transferred(&header.owner))
+    {
+        return Err(SubRegisterError::CannotRevoke.into());
+    }
+
+    let mint_record = match (sub_record.mint_record, item.mint_record) {
+        (None, Some(_)) | (Some(_), None) => return Err(SubRegisterError::MissingMintRecord.into()),
+        (None, None) => None,
+        (Some(mint_record_key), Some(mint_record_account)) => {
+            check_account_key(mint_record_account, &mint_record_key)?;
+            Some(MintRecord::from_account_info(
+                mint_record_account,
+                Tag::MintRecord,
+            )?)
+        }
+    };
+
+    Ok((sub_record, mint_record))
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    check_authority_or_delegate(
+        program_id,
+        accounts.registrar.key,
+        &registrar,
+        accounts.authority,
+        accounts.delegate,
+        Permission::Revoke,
+    )?;
+    check_account_key(accounts.parent_domain, &registrar.domain_account)?;
+
+    let mut results = vec![];
+    let sub_accounts_iter = &mut accounts.sub_accounts.iter();
+    while !sub_accounts_iter.as_slice().is_empty() {
+        let item = Item {
+            sub_domain_account: next_account_info(sub_accounts_iter)?,
+            sub_record: next_account_info(sub_accounts_iter)?,
+            sub_owner: next_account_info(sub_accounts_iter)?,
+            mint_record: next_account_with_tag(sub_accounts_iter, program_id, Tag::MintRecord),
+        };
+
+        // The registrar is saved by `revoke_unchecked` after each revocation
+        let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+        let (sub_record, mint_record) = match check_item(program_id, &registrar, &item) {
+            Ok(res) => res,
+            Err(err) => {
+                msg!("Skipping {}: {}", item.sub_domain_account.key, err);
+                results.push(u64::from(err));
+                continue;
+            }
+        };
+
+        revoke_unchecked::revoke_unchecked(
+            registrar,
+            sub_record,
+            mint_record,
+            false,
+            accounts.registrar,
+            item.sub_domain_account,
+            accounts.parent_domain,
+            accounts.name_class,
+            accounts.spl_name_service,
+            item.sub_record,
+            item.sub_owner,
+            item.mint_record,
+        )?;
+        results.push(REVOKED);
+    }
+
+    if results.is_empty() {
+        return Err(SubRegisterError::MissingAccount.into());
+    }
+
+    set_return_data(&results.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! Allow the authority of a `Registrar` or a delegate to transfer a subdomain to a new owner
+//! e.g to recover a subdomain held by a lost or compromised wallet
+
+use crate::{
+    cpi::Cpi,
+    error::SubRegisterError,
+    state::{delegate::Permission, registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+    utils::{check_authority_or_delegate, next_account_with_tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+    spl_name_service::state::NameRecordHeader,
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The new owner of the subdomain
+    pub new_owner: Pubkey,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The subdomain account to transfer
+    pub sub_domain_account: &'a T,
+
+    #[cons(writable)]
+    /// The subrecord account
+    pub sub_record: &'a T,
+
+    /// The parent domain
+    pub parent_domain: &'a T,
+
+    #[cons(signer)]
+    /// The registrar authority or a delegate with the `Transfer` permission
+    pub authority: &'a T,
+
+    /// Name class
+    pub name_class: &'a T,
+
+    /// The name service program ID
+    pub spl_name_service: &'a T,
+
+    /// The delegate account if the signer is not the registrar authority
+    pub delegate: Option<&'a T>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            registrar: next_account_info(accounts_iter)?,
+            sub_domain_account: next_account_info(accounts_iter)?,
+            sub_record: next_account_info(accounts_iter)?,
+            parent_domain: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            name_class: next_account_info(accounts_iter)?,
+            spl_name_service: next_account_info(accounts_iter)?,
+            delegate: next_account_with_tag(accounts_iter, program_id, Tag::Delegate),
+        };
+
+        // Check keys
+        check_account_key(accounts.name_class, &Pubkey::default())?;
+        check_account_key(accounts.spl_name_service, &spl_name_service::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.sub_domain_account, &spl_name_service::ID)?;
+        check_account_owner(accounts.sub_record, program_id)?;
+        check_account_owner(accounts.parent_domain, &spl_name_service::ID)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+
+    let mut sub_record = SubDomainRecord::from_account_info(accounts.sub_record, Tag::SubRecord)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    if sub_record.is_stale(&registrar) {
+        return Err(SubRegisterError::StaleSubRecord.into());
+    }
+
+    let (subrecord_key, _) = SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
+
+    check_authority_or_delegate(
+        program_id,
+        accounts.registrar.key,
+        &registrar,
+        accounts.authority,
+        accounts.delegate,
+        Permission::Transfer,
+    )?;
+    check_account_key(accounts.sub_record, &subrecord_key)?;
+    check_account_key(accounts.parent_domain, &registrar.domain_account)?;
+
+    if !sub_record.is_revocable(&registrar) {
+        return Err(SubRegisterError::CannotRevoke.into());
+    }
+
+    let header = NameRecordHeader::unpack_from_slice(&accounts.sub_domain_account.data.borrow())?;
+
+    Cpi::transfer_subdomain(
+        &registrar,
+        accounts.registrar,
+        &params.new_owner,
+        accounts.sub_domain_account,
+        accounts.parent_domain,
+        accounts.name_class,
+        accounts.spl_name_service,
+    )?;
+
+    msg!(
+        "Subdomain {} transferred from {} to {} by {}",
+        accounts.sub_domain_account.key,
+        header.owner,
+        params.new_owner,
+        accounts.authority.key
+    );
+
+    // A soulbound subdomain is bound to its new owner
+    if sub_record.bound_owner.is_some() {
+        sub_record.bound_owner = Some(params.new_owner);
+    }
+    sub_record.sync_owner(&params.new_owner, Clock::get()?.unix_timestamp)?;
+
+    // Serialize state
+    sub_record.save(&mut accounts.sub_record.data.borrow_mut());
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Cancel a timelocked registrar edit queued with `EditRegistrar`
+
+use crate::{
+    error::SubRegisterError,
+    state::{pending_change::PendingChange, registry::Registrar, Tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(signer)]
+    /// The registrar authority
+    pub authority: &'a T,
+
+    #[cons(writable)]
+    /// The pending change account
+    pub pending_change: &'a T,
+
+    #[cons(writable)]
+    /// The payer of the pending change, receives its rent
+    pub lamports_target: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            registrar: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            pending_change: next_account_info(accounts_iter)?,
+            lamports_target: next_account_info(accounts_iter)?,
+        };
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.pending_change, program_id)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+    let pending_change =
+        PendingChange::from_account_info(accounts.pending_change, Tag::PendingChange)?;
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+
+    let (pending_change_key, _) = PendingChange::find_key(accounts.registrar.key, program_id);
+    check_account_key(accounts.pending_change, &pending_change_key)?;
+    check_account_key(accounts.lamports_target, &pending_change.payer)?;
+
+    if pending_change.tag != Tag::PendingChange {
+        return Err(SubRegisterError::DataTypeMismatch.into());
+    }
+
+    PendingChange::close(accounts.pending_change, accounts.lamports_target);
+
+    Ok(())
+}
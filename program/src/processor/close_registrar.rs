@@ -1,4 +1,6 @@
 //! Close a registrar account
+//! A registrar with live subdomains can only be closed once wound down, see `WindDown`
+//! The registrar vault is closed along with it, it must be emptied with `Withdraw` first
 use crate::{
     error::SubRegisterError,
     state::{registry::Registrar, Tag},
@@ -12,12 +14,15 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
         msg,
         program::invoke_signed,
         program_error::ProgramError,
+        program_pack::Pack,
         pubkey::Pubkey,
         system_program,
+        sysvar::Sysvar,
     },
 };
 
@@ -50,6 +55,13 @@ pub struct Accounts<'a, T> {
 
     /// The SPL name service program ID
     pub spl_name_program_id: &'a T,
+
+    /// The SPL token program account
+    pub spl_token_program: &'a T,
+
+    #[cons(writable)]
+    /// The registrar vault, may not be initialized
+    pub vault: &'a T,
 }
 
 impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
@@ -66,11 +78,14 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             lamports_target: next_account_info(accounts_iter)?,
             registry_authority: next_account_info(accounts_iter)?,
             spl_name_program_id: next_account_info(accounts_iter)?,
+            spl_token_program: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
         };
 
         // Check keys
         check_account_key(accounts.system_program, &system_program::ID)?;
         check_account_key(accounts.spl_name_program_id, &spl_name_service::ID)?;
+        check_account_key(accounts.spl_token_program, &spl_token::ID)?;
 
         // Check owners
         check_account_owner(accounts.registrar, program_id)?;
@@ -91,8 +106,11 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
     // Checks
     check_account_key(accounts.registry_authority, &registrar.authority)?;
     check_account_key(accounts.domain_name_account, &registrar.domain_account)?;
+    let (vault_key, _) = Registrar::find_vault_key(accounts.registrar.key, program_id);
+    check_account_key(accounts.vault, &vault_key)?;
 
-    if registrar.total_sub_created != 0 {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    if registrar.total_sub_created != 0 && !registrar.is_wound_down(current_timestamp) {
         msg!(
             "Cannot close registry - {} subs are still registered",
             registrar.total_sub_created
@@ -123,6 +141,33 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
         &[seeds],
     )?;
 
+    // Close the vault, the proceeds must have been withdrawn
+    if !accounts.vault.data_is_empty() {
+        check_account_owner(accounts.vault, &spl_token::ID)?;
+        let vault = spl_token::state::Account::unpack(&accounts.vault.data.borrow())?;
+        if vault.amount != 0 {
+            msg!("The vault still holds {} tokens", vault.amount);
+            return Err(SubRegisterError::VaultNotEmpty.into());
+        }
+        let ix = spl_token::instruction::close_account(
+            &spl_token::ID,
+            accounts.vault.key,
+            accounts.lamports_target.key,
+            accounts.registrar.key,
+            &[],
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                accounts.spl_token_program.clone(),
+                accounts.vault.clone(),
+                accounts.lamports_target.clone(),
+                accounts.registrar.clone(),
+            ],
+            &[seeds],
+        )?;
+    }
+
     // Close registry account
     registrar.tag = Tag::ClosedRegistrar;
     registrar.save(&mut accounts.registrar.data.borrow_mut());
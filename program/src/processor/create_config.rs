@@ -0,0 +1,102 @@
+//! Create the global config account
+
+use crate::{
+    cpi::Cpi,
+    error::SubRegisterError,
+    state::{config::Config, FEE_ACC_OWNER, MAX_FEE_BPS},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The admin authority of the config
+    pub admin: Pubkey,
+    /// The protocol fee in basis points
+    pub fee_bps: u64,
+    /// The owner of the token accounts receiving the protocol fees
+    pub fee_recipient: Pubkey,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    #[cons(writable)]
+    /// The config account
+    pub config: &'a T,
+
+    #[cons(signer)]
+    /// The default fee account owner
+    pub default_admin: &'a T,
+
+    #[cons(writable, signer)]
+    /// The fee payer account
+    pub fee_payer: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        _program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+            default_admin: next_account_info(accounts_iter)?,
+            fee_payer: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+        check_account_key(accounts.default_admin, &FEE_ACC_OWNER)?;
+
+        // Check owners
+        check_account_owner(accounts.config, &system_program::ID)?;
+
+        // Check signer
+        check_signer(accounts.default_admin)?;
+        check_signer(accounts.fee_payer)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let (config_key, nonce) = Config::find_key(program_id);
+    check_account_key(accounts.config, &config_key)?;
+
+    if params.fee_bps > MAX_FEE_BPS {
+        return Err(SubRegisterError::InvalidFee.into());
+    }
+
+    let config = Config::new(&params.admin, params.fee_bps, &params.fee_recipient, nonce);
+    let seeds: &[&[u8]] = &[Config::SEEDS, &[nonce]];
+    Cpi::create_account(
+        program_id,
+        accounts.system_program,
+        accounts.fee_payer,
+        accounts.config,
+        seeds,
+        config.borsh_len(),
+    )?;
+    config.save(&mut accounts.config.data.borrow_mut());
+
+    Ok(())
+}
@@ -1,13 +1,15 @@
 //! Create registrar
+//! The domain can be a .sol domain or a subdomain at any depth, e.g `dept.company.sol`
+//! Domains of other TLDs are accepted if their root is allowed by the config
 
 use crate::{
     cpi::Cpi,
     error::SubRegisterError,
     state::{
-        registry::Registrar, schedule::Price, subdomain_record::REVOKE_EXPIRY_DELAY_SECONDS_MIN,
-        ROOT_DOMAIN_ACCOUNT,
+        config::Config, registry::Registrar, schedule::Price,
+        subdomain_record::REVOKE_EXPIRY_DELAY_SECONDS_MIN,
     },
-    utils::is_price_schedule_sorted,
+    utils::{find_domain_root, is_price_schedule_sorted},
 };
 
 use {
@@ -18,6 +20,7 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
         msg,
         program::invoke,
@@ -25,6 +28,7 @@ use {
         program_pack::Pack,
         pubkey::Pubkey,
         system_program,
+        sysvar::Sysvar,
     },
     spl_name_service::state::NameRecordHeader,
 };
@@ -36,7 +40,7 @@ pub struct Params {
     pub authority: Pubkey,
     pub price_schedule: Vec<u8>,
     pub nft_gated_collection: Option<Pubkey>,
-    pub max_nft_mint: u8,
+    pub max_nft_mint: u32,
     pub allow_revoke: bool,
     pub revoke_expiry_delay: i64,
 }
@@ -64,12 +68,18 @@ pub struct Accounts<'a, T> {
 
     /// The SPL name service program ID
     pub spl_name_program_id: &'a T,
+
+    /// The global config account, may not be initialized
+    pub config: &'a T,
+
+    /// The parent domains of the domain up to the top level domain, required for subdomains
+    pub ancestor_domains: &'a [T],
 }
 
 impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
     pub fn parse(
         accounts: &'a [AccountInfo<'b>],
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
     ) -> Result<Self, ProgramError> {
         let accounts_iter = &mut accounts.iter();
         let accounts = Accounts {
@@ -79,11 +89,14 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             domain_owner: next_account_info(accounts_iter)?,
             fee_payer: next_account_info(accounts_iter)?,
             spl_name_program_id: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+            ancestor_domains: accounts_iter.as_slice(),
         };
 
         // Check keys
         check_account_key(accounts.system_program, &system_program::ID)?;
         check_account_key(accounts.spl_name_program_id, &spl_name_service::ID)?;
+        check_account_key(accounts.config, &Config::find_key(program_id).0)?;
 
         // Check owners
         check_account_owner(accounts.registrar, &system_program::ID).unwrap();
@@ -105,10 +118,12 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     // Checks
     let name_header =
         NameRecordHeader::unpack_from_slice(&accounts.domain_name_account.data.borrow())?;
-    if name_header.parent_name != ROOT_DOMAIN_ACCOUNT {
-        msg!("Only .sol are accepted");
-        return Err(SubRegisterError::WrongNameAccount.into());
-    }
+    let root = find_domain_root(&name_header, accounts.ancestor_domains)?;
+    let config = Config::from_account_info_or_default(accounts.config, program_id)?;
+    let root_domain = match config.get_root_domain(&root) {
+        Some(root_domain) => root_domain,
+        None => return Err(SubRegisterError::RootDomainNotAllowed.into()),
+    };
 
     let price_schedule: Vec<Price> =
         BorshDeserialize::deserialize(&mut params.price_schedule.as_slice())?;
@@ -129,7 +144,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
         &accounts.domain_name_account.key.to_bytes(),
         &[nonce],
     ];
-    let registry = Registrar::new(
+    let mut registry = Registrar::new(
         &params.authority,
         &params.fee_account,
         &params.mint,
@@ -141,6 +156,8 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
         params.allow_revoke,
         params.revoke_expiry_delay,
     );
+    registry.created_at = Clock::get()?.unix_timestamp;
+    registry.root_domain = root_domain;
     Cpi::create_account(
         program_id,
         accounts.system_program,
@@ -0,0 +1,114 @@
+//! Create a token vault owned by the registrar to collect the proceeds
+//! The proceeds are collected once the fee account is set to the vault with `EditRegistrar`,
+//! which is subject to the timelock of the registrar
+
+use crate::{
+    cpi::Cpi,
+    state::{registry::Registrar, Tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program, sysvar,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    /// The SPL token program account
+    pub spl_token_program: &'a T,
+
+    /// The rent sysvar account
+    pub rent_sysvar: &'a T,
+
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The vault token account to create
+    pub vault: &'a T,
+
+    /// The mint of the registrar
+    pub mint: &'a T,
+
+    #[cons(writable, signer)]
+    /// The authority of the registrar
+    pub authority: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            spl_token_program: next_account_info(accounts_iter)?,
+            rent_sysvar: next_account_info(accounts_iter)?,
+            registrar: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+        check_account_key(accounts.spl_token_program, &spl_token::ID)?;
+        check_account_key(accounts.rent_sysvar, &sysvar::rent::id())?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.vault, &system_program::ID)?;
+        check_account_owner(accounts.mint, &spl_token::ID)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+    check_account_key(accounts.mint, &registrar.mint)?;
+
+    let (vault_key, vault_nonce) = Registrar::find_vault_key(accounts.registrar.key, program_id);
+    check_account_key(accounts.vault, &vault_key)?;
+
+    let seeds: &[&[u8]] = &[
+        Registrar::VAULT_SEEDS,
+        &accounts.registrar.key.to_bytes(),
+        &[vault_nonce],
+    ];
+    Cpi::allocate_and_create_token_account(
+        accounts.registrar.key,
+        accounts.spl_token_program,
+        accounts.authority,
+        seeds,
+        accounts.vault,
+        accounts.mint,
+        accounts.rent_sysvar,
+        accounts.system_program,
+    )?;
+
+    Ok(())
+}
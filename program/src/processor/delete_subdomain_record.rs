@@ -1,4 +1,5 @@
 //! Delete a subrecord account account
+//! Once the registrar is wound down, the records of live subdomains can be deleted by anyone
 use solana_program::{clock::Clock, sysvar::Sysvar};
 
 use crate::{
@@ -63,8 +64,6 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
         // Check keys
 
         // Check owners
-        check_account_owner(accounts.registrar, program_id)?;
-        check_account_owner(accounts.sub_domain, &system_program::ID)?;
         check_account_owner(accounts.sub_record, program_id)?;
 
         // Check signer
@@ -85,9 +84,29 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
         }
         _ => return Err(SubRegisterError::DataTypeMismatch.into()),
     };
-    let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
     check_account_key(accounts.registrar, &sub_record.registrar)?;
 
+    // The registrar can only have been closed with live subdomains after winding down
+    let registrar = if accounts.registrar.owner == program_id {
+        Some(Registrar::from_account_info(
+            accounts.registrar,
+            Tag::Registrar,
+        )?)
+    } else {
+        None
+    };
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let is_wound_down = registrar
+        .as_ref()
+        .map(|r| r.is_wound_down(current_timestamp))
+        .unwrap_or(true);
+    if !is_wound_down {
+        check_account_owner(accounts.sub_domain, &system_program::ID)?;
+    }
+
+    // Stale records are not accounted for by the current registrar
+    let mut registrar = registrar.filter(|r| !sub_record.is_stale(r));
+
     // Check PDA derivation
     let (sub_record_key, _) = SubDomainRecord::find_key(accounts.sub_domain.key, program_id);
     check_account_key(accounts.sub_record, &sub_record_key)?;
@@ -121,11 +140,13 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
     **lamports = 0;
 
     // Edit Registrar
-    registrar.total_sub_created = registrar
-        .total_sub_created
-        .checked_sub(1)
-        .ok_or(SubRegisterError::Overflow)?;
-    registrar.save(&mut accounts.registrar.data.borrow_mut());
+    if let Some(registrar) = registrar.as_mut() {
+        registrar.total_sub_created = registrar
+            .total_sub_created
+            .checked_sub(1)
+            .ok_or(SubRegisterError::Overflow)?;
+        registrar.save(&mut accounts.registrar.data.borrow_mut());
+    }
 
     Ok(())
 }
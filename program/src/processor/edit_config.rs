@@ -0,0 +1,141 @@
+//! Edit the global config account
+
+use crate::{
+    error::SubRegisterError,
+    state::{
+        config::{Config, FeeOverride, RootDomain},
+        Tag, MAX_FEE_BPS,
+    },
+    utils::realloc_account,
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    pub new_admin: Option<Pubkey>,
+    pub new_fee_bps: Option<u64>,
+    pub new_fee_recipient: Option<Pubkey>,
+    /// Adds or replaces the protocol fee of a registrar
+    pub set_fee_override: Option<FeeOverride>,
+    /// Removes the protocol fee override of a registrar
+    pub remove_fee_override: Option<Pubkey>,
+    /// Allows or replaces a TLD root under which registrars can be created
+    pub set_root_domain: Option<RootDomain>,
+    /// Removes a TLD root, the registrars already created under it are not affected
+    pub remove_root_domain: Option<Pubkey>,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    #[cons(writable, signer)]
+    /// The admin of the config
+    pub admin: &'a T,
+
+    #[cons(writable)]
+    /// The config account
+    pub config: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            admin: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.config, program_id)?;
+
+        // Check signer
+        check_signer(accounts.admin)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let mut config = Config::from_account_info(accounts.config, Tag::Config)?;
+
+    check_account_key(accounts.admin, &config.admin)?;
+
+    if let Some(new_admin) = params.new_admin {
+        config.admin = new_admin;
+    }
+
+    if let Some(new_fee_bps) = params.new_fee_bps {
+        if new_fee_bps > MAX_FEE_BPS {
+            return Err(SubRegisterError::InvalidFee.into());
+        }
+        config.fee_bps = new_fee_bps;
+    }
+
+    if let Some(new_fee_recipient) = params.new_fee_recipient {
+        config.fee_recipient = new_fee_recipient;
+    }
+
+    if let Some(registrar) = params.remove_fee_override {
+        config.fee_overrides.retain(|o| o.registrar != registrar);
+    }
+
+    if let Some(fee_override) = params.set_fee_override {
+        if fee_override.fee_bps > MAX_FEE_BPS {
+            return Err(SubRegisterError::InvalidFee.into());
+        }
+        config
+            .fee_overrides
+            .retain(|o| o.registrar != fee_override.registrar);
+        config.fee_overrides.push(fee_override);
+    }
+
+    if let Some(name_account) = params.remove_root_domain {
+        config
+            .root_domains
+            .retain(|r| r.name_account != name_account);
+    }
+
+    if let Some(root_domain) = params.set_root_domain {
+        config
+            .root_domains
+            .retain(|r| r.name_account != root_domain.name_account);
+        config.root_domains.push(root_domain);
+    }
+
+    // Handle realloc
+    realloc_account(
+        accounts.config,
+        config.borsh_len(),
+        accounts.admin,
+        accounts.system_program,
+    )?;
+
+    // Serialize state
+    config.save(&mut accounts.config.data.borrow_mut());
+
+    Ok(())
+}
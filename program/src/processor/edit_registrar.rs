@@ -1,8 +1,27 @@
 //! Edit a registrar
+//! Delegates with the `EditPricing` permission can only edit the price schedule, the bonding curve
+//! and the irrevocable premium
+//! When the registrar has a timelock, sensitive edits are queued in a `PendingChange` account
+//! and applied with `ExecuteChange` once the delay has elapsed
+//! Revocation can only be disabled and the revoke expiry delay can only be lengthened
+//! Lowering `max_nft_mint` only limits new registrations, existing subdomains are kept
 
 use crate::{
-    state::{registry::Registrar, schedule::Price, Tag},
-    utils::is_price_schedule_sorted,
+    cpi::Cpi,
+    error::SubRegisterError,
+    state::{
+        bonding_curve::{BondingCurve, MULTIPLIER_BASE_BPS},
+        delegate::Permission,
+        pending_change::{PendingChange, RegistrarChange},
+        registry::{NftGatePolicy, RefundPolicy, Registrar},
+        schedule::Price,
+        subdomain_record::REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        Tag,
+    },
+    utils::{
+        check_authority_or_delegate, check_refund_vault, is_price_schedule_cheaper,
+        is_price_schedule_sorted, next_account_with_tag, realloc_account,
+    },
 };
 
 use {
@@ -14,26 +33,40 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
         msg,
-        program::invoke,
         program_error::ProgramError,
         pubkey::Pubkey,
-        rent::Rent,
-        system_instruction, system_program,
+        system_program,
         sysvar::Sysvar,
     },
-    std::cmp::Ordering,
 };
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSize)]
 pub struct Params {
-    /// The new registry
-    pub new_authority: Option<Pubkey>,
     pub new_mint: Option<Pubkey>,
     pub new_fee_account: Option<Pubkey>,
     pub new_price_schedule: Option<Vec<u8>>,
-    pub new_max_nft_mint: Option<u8>,
+    pub new_max_nft_mint: Option<u32>,
+    /// The refund policy applied on unregistration
+    pub new_refund_policy: Option<RefundPolicy>,
+    /// The bonding curve applied to the price schedule
+    pub new_bonding_curve: Option<BondingCurve>,
+    /// Whether new subdomains are bound to the wallet they are registered to
+    pub new_soulbound: Option<bool>,
+    /// The delay in seconds before sensitive edits take effect, 0 to disable the timelock
+    pub new_timelock_delay: Option<i64>,
+    /// Disables the revocation of subdomains, revocation cannot be enabled again
+    pub new_allow_revoke: Option<bool>,
+    /// The delay in seconds before a revoked subdomain can be registered again, cannot decrease
+    pub new_revoke_expiry_time: Option<i64>,
+    /// The price multiplier in basis points for irrevocable subdomains, 0 to stop offering them
+    pub new_irrevocable_premium_bps: Option<u64>,
+    /// Adds, replaces or removes (`Some(None)`) the gated NFT collection
+    pub new_nft_gated_collection: Option<Option<Pubkey>>,
+    /// The policy applied to subdomains minted with NFTs outside of the gated collection
+    pub new_nft_gate_policy: Option<NftGatePolicy>,
 }
 
 #[derive(InstructionsAccount)]
@@ -42,12 +75,19 @@ pub struct Accounts<'a, T> {
     pub system_program: &'a T,
 
     #[cons(writable, signer)]
-    /// The fee payer account
+    /// The registrar authority or a delegate with the `EditPricing` permission
     pub authority: &'a T,
 
     #[cons(writable)]
     /// The registry to edit
     pub registrar: &'a T,
+
+    /// The delegate account if the signer is not the registrar authority
+    pub delegate: Option<&'a T>,
+
+    #[cons(writable)]
+    /// The pending change account, required when sensitive edits are timelocked
+    pub pending_change: Option<&'a T>,
 }
 
 impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
@@ -60,6 +100,8 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             system_program: next_account_info(accounts_iter)?,
             authority: next_account_info(accounts_iter)?,
             registrar: next_account_info(accounts_iter)?,
+            delegate: next_account_with_tag(accounts_iter, program_id, Tag::Delegate),
+            pending_change: next_account_info(accounts_iter).ok(),
         };
 
         // Check keys
@@ -79,19 +121,38 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     let accounts = Accounts::parse(accounts, program_id)?;
     let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
 
-    check_account_key(accounts.authority, &registrar.authority)?;
-
-    if let Some(new_authority) = params.new_authority {
-        registrar.authority = new_authority;
+    if accounts.authority.key != &registrar.authority {
+        check_authority_or_delegate(
+            program_id,
+            accounts.registrar.key,
+            &registrar,
+            accounts.authority,
+            accounts.delegate,
+            Permission::EditPricing,
+        )?;
+        let edits_pricing_only = params.new_mint.is_none()
+            && params.new_fee_account.is_none()
+            && params.new_max_nft_mint.is_none()
+            && params.new_refund_policy.is_none()
+            && params.new_soulbound.is_none()
+            && params.new_timelock_delay.is_none()
+            && params.new_allow_revoke.is_none()
+            && params.new_revoke_expiry_time.is_none()
+            && params.new_nft_gated_collection.is_none()
+            && params.new_nft_gate_policy.is_none();
+        if !edits_pricing_only {
+            return Err(SubRegisterError::MissingPermission.into());
+        }
     }
 
-    if let Some(new_mint) = params.new_mint {
-        registrar.mint = new_mint;
-    }
-
-    if let Some(new_fee_account) = params.new_fee_account {
-        registrar.fee_account = new_fee_account;
-    }
+    // Edits which cannot hurt subdomain owners are applied right away,
+    // the others are subject to the timelock
+    let mut change = RegistrarChange {
+        new_mint: params.new_mint,
+        new_fee_account: params.new_fee_account,
+        new_nft_gated_collection: params.new_nft_gated_collection,
+        ..Default::default()
+    };
 
     if let Some(new_price_schedule_ser) = params.new_price_schedule {
         let new_price_schedule: Vec<Price> =
@@ -101,56 +162,155 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
             msg!("The schedule price array should be sorted!");
             return Err(ProgramError::InvalidArgument);
         }
-        registrar.price_schedule = new_price_schedule;
+        if is_price_schedule_cheaper(&registrar.price_schedule, &new_price_schedule) {
+            registrar.price_schedule = new_price_schedule;
+        } else {
+            change.new_price_schedule = Some(new_price_schedule);
+        }
     }
 
     if let Some(new_max_nft_mint) = params.new_max_nft_mint {
         registrar.max_nft_mint = new_max_nft_mint;
     }
 
-    // Handle realloc
-    match registrar.borsh_len().cmp(&accounts.registrar.data_len()) {
-        Ordering::Greater => {
-            msg!("[+] Realloc registry account (increasing size)");
-            let new_lamports = Rent::get()?.minimum_balance(registrar.borsh_len());
-            let diff_lamports = new_lamports.checked_sub(accounts.registrar.lamports());
-
-            accounts.registrar.realloc(registrar.borsh_len(), false)?;
-
-            if let Some(diff_lamports) = diff_lamports {
-                let ix = system_instruction::transfer(
-                    accounts.authority.key,
-                    accounts.registrar.key,
-                    diff_lamports,
-                );
-                invoke(
-                    &ix,
-                    &[
-                        accounts.system_program.clone(),
-                        accounts.authority.clone(),
-                        accounts.registrar.clone(),
-                    ],
-                )?;
-            }
+    if let Some(new_refund_policy) = params.new_refund_policy {
+        if new_refund_policy.window < 0 {
+            msg!("The refund window cannot be negative");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if new_refund_policy.covers(&registrar.refund_policy) {
+            registrar.refund_policy = new_refund_policy;
+        } else {
+            change.new_refund_policy = Some(new_refund_policy);
+        }
+    }
+
+    if let Some(new_bonding_curve) = params.new_bonding_curve {
+        if !new_bonding_curve.is_valid() {
+            msg!("Invalid bonding curve");
+            return Err(ProgramError::InvalidArgument);
+        }
+        change.new_bonding_curve = Some(new_bonding_curve);
+    }
+
+    match params.new_soulbound {
+        Some(true) => change.new_soulbound = Some(true),
+        Some(false) => registrar.soulbound = false,
+        None => {}
+    }
+
+    match params.new_nft_gate_policy {
+        Some(NftGatePolicy::Revocable) => {
+            change.new_nft_gate_policy = Some(NftGatePolicy::Revocable)
+        }
+        Some(NftGatePolicy::Grandfathered) => {
+            registrar.nft_gate_policy = NftGatePolicy::Grandfathered
+        }
+        None => {}
+    }
+
+    if let Some(new_allow_revoke) = params.new_allow_revoke {
+        if new_allow_revoke && !registrar.allow_revoke {
+            msg!("Revocation cannot be enabled again");
+            return Err(ProgramError::InvalidArgument);
+        }
+        registrar.allow_revoke = new_allow_revoke;
+    }
+
+    if let Some(new_revoke_expiry_time) = params.new_revoke_expiry_time {
+        if new_revoke_expiry_time < REVOKE_EXPIRY_DELAY_SECONDS_MIN
+            || new_revoke_expiry_time < registrar.revoke_expiry_time
+        {
+            msg!("The revoke expiry delay can only be lengthened");
+            return Err(SubRegisterError::RevokeExpiryDelayTooLow.into());
+        }
+        registrar.revoke_expiry_time = new_revoke_expiry_time;
+    }
+
+    if let Some(new_irrevocable_premium_bps) = params.new_irrevocable_premium_bps {
+        if new_irrevocable_premium_bps != 0 && new_irrevocable_premium_bps < MULTIPLIER_BASE_BPS {
+            msg!("The irrevocable premium cannot discount the price");
+            return Err(ProgramError::InvalidArgument);
         }
-        Ordering::Less => {
-            msg!("[+] Realloc registry account (decreasing size)");
-            let new_lamports = Rent::get()?.minimum_balance(registrar.borsh_len());
-            let diff_lamports = accounts.registrar.lamports().checked_sub(new_lamports);
+        registrar.irrevocable_premium_bps = new_irrevocable_premium_bps;
+    }
 
-            accounts.registrar.realloc(registrar.borsh_len(), true)?;
+    check_refund_vault(
+        change
+            .new_refund_policy
+            .as_ref()
+            .unwrap_or(&registrar.refund_policy),
+        change
+            .new_fee_account
+            .as_ref()
+            .unwrap_or(&registrar.fee_account),
+        accounts.registrar.key,
+        program_id,
+    )?;
 
-            if let Some(diff_lamports) = diff_lamports {
-                let mut registrar_lamports = accounts.registrar.lamports.borrow_mut();
-                let mut authority_lamports = accounts.authority.lamports.borrow_mut();
+    let timelock_delay = registrar.timelock_delay;
 
-                **authority_lamports += diff_lamports;
-                **registrar_lamports -= diff_lamports;
-            }
+    if let Some(new_timelock_delay) = params.new_timelock_delay {
+        if new_timelock_delay < 0 {
+            msg!("The timelock delay cannot be negative");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if new_timelock_delay >= timelock_delay {
+            registrar.timelock_delay = new_timelock_delay;
+        } else {
+            change.new_timelock_delay = Some(new_timelock_delay);
         }
-        Ordering::Equal => (),
     }
 
+    if timelock_delay == 0 {
+        change.apply(&mut registrar);
+    } else if !change.is_empty() {
+        let pending_change_account = accounts
+            .pending_change
+            .ok_or(SubRegisterError::MissingAccount)?;
+        let (pending_change_key, nonce) =
+            PendingChange::find_key(accounts.registrar.key, program_id);
+        check_account_key(pending_change_account, &pending_change_key)?;
+        if !pending_change_account.data_is_empty() {
+            msg!("A change is already pending");
+            return Err(SubRegisterError::AlreadyInitialized.into());
+        }
+
+        let execution_timestamp = Clock::get()?
+            .unix_timestamp
+            .checked_add(timelock_delay)
+            .ok_or(SubRegisterError::Overflow)?;
+        msg!("[+] Change queued until {}", execution_timestamp);
+        let pending_change = PendingChange::new(
+            accounts.registrar.key,
+            accounts.authority.key,
+            execution_timestamp,
+            change,
+        );
+        let seeds: &[&[u8]] = &[
+            PendingChange::SEEDS,
+            &accounts.registrar.key.to_bytes(),
+            &[nonce],
+        ];
+        Cpi::create_account(
+            program_id,
+            accounts.system_program,
+            accounts.authority,
+            pending_change_account,
+            seeds,
+            pending_change.borsh_len(),
+        )?;
+        pending_change.save(&mut pending_change_account.data.borrow_mut());
+    }
+
+    // Handle realloc
+    realloc_account(
+        accounts.registrar,
+        registrar.borsh_len(),
+        accounts.authority,
+        accounts.system_program,
+    )?;
+
     // Serialize state
     registrar.save(&mut accounts.registrar.data.borrow_mut());
 
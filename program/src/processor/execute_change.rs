@@ -0,0 +1,121 @@
+//! Apply a timelocked registrar edit once its delay has elapsed
+//! This instruction is permissionless, the rent of the pending change is refunded to its payer
+
+use crate::{
+    error::SubRegisterError,
+    state::{pending_change::PendingChange, registry::Registrar, Tag},
+    utils::{check_refund_vault, realloc_account},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+        sysvar::Sysvar,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    #[cons(writable)]
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The pending change account
+    pub pending_change: &'a T,
+
+    #[cons(writable)]
+    /// The payer of the pending change, receives its rent
+    pub lamports_target: &'a T,
+
+    #[cons(writable, signer)]
+    /// The fee payer account, funds the registrar realloc
+    pub fee_payer: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            registrar: next_account_info(accounts_iter)?,
+            pending_change: next_account_info(accounts_iter)?,
+            lamports_target: next_account_info(accounts_iter)?,
+            fee_payer: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.pending_change, program_id)?;
+
+        // Check signer
+        check_signer(accounts.fee_payer)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+    let pending_change =
+        PendingChange::from_account_info(accounts.pending_change, Tag::PendingChange)?;
+
+    let (pending_change_key, _) = PendingChange::find_key(accounts.registrar.key, program_id);
+    check_account_key(accounts.pending_change, &pending_change_key)?;
+    check_account_key(accounts.lamports_target, &pending_change.payer)?;
+
+    if pending_change.tag != Tag::PendingChange {
+        return Err(SubRegisterError::DataTypeMismatch.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now < pending_change.execution_timestamp {
+        return Err(SubRegisterError::TimelockNotExpired.into());
+    }
+
+    pending_change.change.apply(&mut registrar);
+    check_refund_vault(
+        &registrar.refund_policy,
+        &registrar.fee_account,
+        accounts.registrar.key,
+        program_id,
+    )?;
+
+    // Handle realloc
+    realloc_account(
+        accounts.registrar,
+        registrar.borsh_len(),
+        accounts.fee_payer,
+        accounts.system_program,
+    )?;
+
+    // Serialize state
+    registrar.save(&mut accounts.registrar.data.borrow_mut());
+
+    PendingChange::close(accounts.pending_change, accounts.lamports_target);
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Migrate an account using an outdated layout to the current layout
+//! This instruction is permissionless, the fee payer funds the rent of the resized account
+
+use crate::{
+    state::{
+        config::Config, delegate::Delegate, mint_record::MintRecord, pending_change::PendingChange,
+        registry::Registrar, subdomain_record::SubDomainRecord, Tag, Versioned,
+    },
+    utils::realloc_account,
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    #[cons(writable)]
+    /// The account to migrate
+    pub account: &'a T,
+
+    #[cons(writable, signer)]
+    /// The fee payer account
+    pub fee_payer: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            account: next_account_info(accounts_iter)?,
+            fee_payer: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.account, program_id)?;
+
+        // Check signer
+        check_signer(accounts.fee_payer)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+
+    let tag = Tag::from_u8(accounts.account.data.borrow()[0]).map(|t| t.current());
+    match tag {
+        Some(Tag::Registrar) => migrate::<Registrar>(&accounts),
+        Some(Tag::MintRecord) => migrate::<MintRecord>(&accounts),
+        Some(Tag::Config) => migrate::<Config>(&accounts),
+        Some(Tag::Delegate) => migrate::<Delegate>(&accounts),
+        Some(Tag::PendingChange) => migrate::<PendingChange>(&accounts),
+        Some(Tag::SubRecord | Tag::ClosedSubRecord | Tag::RevokedSubRecord) => {
+            migrate::<SubDomainRecord>(&accounts)
+        }
+        _ => {
+            msg!("The account does not have a versioned layout");
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
+
+fn migrate<T: Versioned>(accounts: &Accounts<AccountInfo>) -> ProgramResult {
+    let version = T::version_of(&accounts.account.data.borrow());
+    if version >= T::VERSION {
+        msg!("The account does not need to be migrated");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let state = T::upgrade(version, &accounts.account.data.borrow())?;
+    realloc_account(
+        accounts.account,
+        state.borsh_len(),
+        accounts.fee_payer,
+        accounts.system_program,
+    )?;
+    state.serialize(&mut &mut accounts.account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
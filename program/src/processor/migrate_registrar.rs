@@ -0,0 +1,103 @@
+//! Move the accounting of a subdomain registered with a previous registrar at the same address
+//! to the current registrar, i.e after the registrar was closed with live subdomains and the
+//! parent domain was placed in a new registrar
+//! The purchase price was received by the previous registrar: the subdomain cannot be refunded
+
+use crate::{
+    error::SubRegisterError,
+    state::{registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    #[cons(writable)]
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The subdomain record account
+    pub sub_record: &'a T,
+
+    #[cons(signer)]
+    /// The registrar authority
+    pub authority: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            registrar: next_account_info(accounts_iter)?,
+            sub_record: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.sub_record, program_id)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+    let mut sub_record = SubDomainRecord::from_account_info_opt(accounts.sub_record, None)?;
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+    check_account_key(accounts.registrar, &sub_record.registrar)?;
+
+    // Revoked subdomains are still accounted for until their record is deleted
+    if !matches!(sub_record.tag, Tag::SubRecord | Tag::RevokedSubRecord) {
+        return Err(SubRegisterError::DataTypeMismatch.into());
+    }
+    if !sub_record.is_stale(&registrar) {
+        msg!("The subdomain record already belongs to the registrar");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The previous registrar kept the proceeds, the subdomain is no longer refundable
+    msg!(
+        "[+] Migrating {}, the refund of {} tokens is forfeited",
+        sub_record.sub_key,
+        sub_record.price_paid
+    );
+    sub_record.registered_at = registrar.created_at;
+    sub_record.price_paid = 0;
+
+    registrar.total_sub_created = registrar
+        .total_sub_created
+        .checked_add(1)
+        .ok_or(SubRegisterError::Overflow)?;
+
+    // Serialize state
+    sub_record.save(&mut accounts.sub_record.data.borrow_mut());
+    registrar.save(&mut accounts.registrar.data.borrow_mut());
+
+    Ok(())
+}
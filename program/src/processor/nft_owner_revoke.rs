@@ -1,11 +1,20 @@
 //! In the case of ...
+//! Subdomains minted with an NFT which left the gated collection can be revoked by anyone
+//! when the `NftGatePolicy` of the registrar is `Revocable` and the subdomain is revocable
+//! Soulbound subdomains transferred away from their bound owner can be revoked by anyone
+//! Disabling revocation on the registrar disables these two cases, not the reclaim by the NFT holder
 
 use mpl_token_metadata::accounts::Metadata;
 
 use crate::{
     error::SubRegisterError,
     revoke_unchecked,
-    state::{mint_record::MintRecord, registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+    state::{
+        mint_record::MintRecord,
+        registry::{NftGatePolicy, Registrar},
+        subdomain_record::SubDomainRecord,
+        Tag,
+    },
     utils::{check_metadata, check_nft_holding_and_get_mint},
 };
 
@@ -19,8 +28,10 @@ use {
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
         program_error::ProgramError,
+        program_pack::Pack,
         pubkey::Pubkey,
     },
+    spl_name_service::state::NameRecordHeader,
 };
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSize)]
@@ -40,6 +51,7 @@ pub struct Accounts<'a, T> {
     /// The subrecord account
     pub sub_record: &'a T,
 
+    #[cons(writable)]
     /// The current sub domain owner
     pub sub_owner: &'a T,
 
@@ -107,33 +119,72 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
 
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
     let accounts = Accounts::parse(accounts, program_id)?;
-
     let sub_record = SubDomainRecord::from_account_info(accounts.sub_record, Tag::SubRecord)?;
     let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
     let mint_record = MintRecord::from_account_info(accounts.nft_mint_record, Tag::MintRecord)?;
-    let collection = registrar
-        .nft_gated_collection
-        .ok_or(SubRegisterError::MustHaveCollection)?;
 
-    let mint = check_nft_holding_and_get_mint(accounts.nft_account, accounts.nft_owner.key)?;
-    check_metadata(accounts.nft_metadata, &collection)?;
+    if sub_record.is_stale(&registrar) {
+        return Err(SubRegisterError::StaleSubRecord.into());
+    }
 
-    let (pda, _) = Metadata::find_pda(&mint);
-    check_account_key(accounts.nft_metadata, &pda)?;
     let (subrecord_key, _) = SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
-
     check_account_key(accounts.sub_record, &subrecord_key)?;
     check_account_key(accounts.parent_domain, &registrar.domain_account)?;
 
     if let Some(sub_mint_rec) = sub_record.mint_record {
         check_account_key(accounts.nft_mint_record, &sub_mint_rec)?;
-        if mint != mint_record.mint {
-            return Err(SubRegisterError::WrongMint.into());
-        }
     } else {
         return Err(SubRegisterError::WrongMintRecord.into());
     }
 
+    let (pda, _) = Metadata::find_pda(&mint_record.mint);
+    check_account_key(accounts.nft_metadata, &pda)?;
+
+    let in_collection = registrar
+        .nft_gated_collection
+        .map(|collection| check_metadata(accounts.nft_metadata, &collection).is_ok())
+        .unwrap_or(false);
+
+    let header = NameRecordHeader::unpack_from_slice(&accounts.sub_domain_account.data.borrow())?;
+
+    // The NFT no longer grants the subdomain, unless the subdomain cannot be revoked
+    let gate_lapsed = !in_collection
+        && registrar.nft_gate_policy == NftGatePolicy::Revocable
+        && sub_record.is_revocable(&registrar);
+    // The soulbound subdomain was transferred
+    let transferred = registrar.allow_revoke
+        && !sub_record.irrevocable
+        && sub_record.is_transferred(&header.owner);
+
+    if gate_lapsed || transferred {
+        check_account_key(accounts.sub_owner, &header.owner)?;
+
+        revoke_unchecked::revoke_unchecked(
+            registrar,
+            sub_record,
+            Some(mint_record),
+            false,
+            accounts.registrar,
+            accounts.sub_domain_account,
+            accounts.parent_domain,
+            accounts.name_class,
+            accounts.spl_name_service,
+            accounts.sub_record,
+            accounts.sub_owner,
+            Some(accounts.nft_mint_record),
+        )?;
+
+        return Ok(());
+    }
+
+    // The NFT holder reclaims the subdomain, the mint record proves the NFT was in the gated
+    // collection at registration
+    let mint = check_nft_holding_and_get_mint(accounts.nft_account, accounts.nft_owner.key)?;
+    if mint != mint_record.mint {
+        return Err(SubRegisterError::WrongMint.into());
+    }
+
     revoke_unchecked::revoke_unchecked(
         registrar,
         sub_record,
@@ -0,0 +1,89 @@
+//! Propose a new authority for a `Registrar`
+//! The new authority only takes effect once it is accepted with `AcceptAuthority`
+
+use crate::{
+    state::{registry::Registrar, Tag},
+    utils::realloc_account,
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The proposed authority
+    pub new_authority: Pubkey,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    #[cons(writable, signer)]
+    /// The current authority of the registrar
+    pub authority: &'a T,
+
+    #[cons(writable)]
+    /// The registrar account
+    pub registrar: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            registrar: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+
+    registrar.pending_authority = Some(params.new_authority);
+
+    // Handle realloc
+    realloc_account(
+        accounts.registrar,
+        registrar.borsh_len(),
+        accounts.authority,
+        accounts.system_program,
+    )?;
+
+    // Serialize state
+    registrar.save(&mut accounts.registrar.data.borrow_mut());
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+//! Quote the registration price of a subdomain
+//! The quote is returned via the return data and can be fetched by simulating the instruction
+
+use mpl_token_metadata::accounts::Metadata;
+
+use crate::{
+    error::SubRegisterError,
+    state::{
+        config::Config, mint_record::MintRecord, registry::Registrar,
+        subdomain_record::SubDomainRecord, Tag,
+    },
+    utils::{
+        check_metadata, check_nft_holding_and_get_mint, check_subdomain_name, get_protocol_fee,
+        get_registration_price, get_subdomain_key,
+    },
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        program::set_return_data,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+        sysvar::Sysvar,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The subdomain to quote
+    pub domain: String,
+    /// Quotes an irrevocable subdomain
+    pub irrevocable: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
+pub enum Eligibility {
+    Eligible,
+    InvalidName,
+    AlreadyRegistered,
+    RevokedNotExpired,
+    MustProvideNft,
+    InvalidNft,
+    MintLimitReached,
+    IrrevocableNotOffered,
+    WindingDown,
+}
+
+/// The result of the quote, written in the return data
+#[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq)]
+pub struct Quote {
+    /// The total price paid by the buyer
+    pub price: u64,
+    /// The amount received by the registrar
+    pub registrar_proceeds: u64,
+    /// The protocol fee
+    pub protocol_fee: u64,
+    /// Whether the buyer can register the subdomain
+    pub eligibility: Eligibility,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The registrar account
+    pub registrar: &'a T,
+
+    /// The global config account, may not be initialized
+    pub config: &'a T,
+
+    /// The subdomain account to quote
+    pub sub_domain_account: &'a T,
+
+    /// The subrecord account
+    pub sub_record: &'a T,
+
+    /// The buyer of the subdomain
+    pub buyer: &'a T,
+
+    /// Optional NFT account if Registrar is NFT gated
+    pub nft_account: Option<&'a T>,
+
+    /// Optional NFT metadata account if Registrar is NFT gated
+    pub nft_metadata_account: Option<&'a T>,
+
+    /// Optional NFT mint record if Registrar is NFT gated
+    pub nft_mint_record: Option<&'a T>,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            registrar: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+            sub_domain_account: next_account_info(accounts_iter)?,
+            sub_record: next_account_info(accounts_iter)?,
+            buyer: next_account_info(accounts_iter)?,
+            nft_account: next_account_info(accounts_iter).ok(),
+            nft_metadata_account: next_account_info(accounts_iter).ok(),
+            nft_mint_record: next_account_info(accounts_iter).ok(),
+        };
+
+        // Check keys
+        check_account_key(accounts.config, &Config::find_key(program_id).0)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+
+        Ok(accounts)
+    }
+}
+
+fn get_eligibility(
+    program_id: &Pubkey,
+    accounts: &Accounts<AccountInfo>,
+    registrar: &Registrar,
+    domain: &str,
+) -> Result<Eligibility, ProgramError> {
+    if registrar.is_winding_down() {
+        return Ok(Eligibility::WindingDown);
+    }
+
+    if check_subdomain_name(domain).is_err() {
+        return Ok(Eligibility::InvalidName);
+    }
+
+    let sub_key = get_subdomain_key(
+        domain.strip_prefix('\x00').unwrap(),
+        &registrar.domain_account,
+    );
+    check_account_key(accounts.sub_domain_account, &sub_key)?;
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_key, program_id);
+    check_account_key(accounts.sub_record, &subrecord_key)?;
+
+    if accounts.sub_domain_account.owner != &system_program::ID {
+        return Ok(Eligibility::AlreadyRegistered);
+    }
+
+    if accounts.sub_record.owner == program_id
+        && accounts
+            .sub_record
+            .data
+            .borrow()
+            .first()
+            .and_then(|t| Tag::from_u8(*t))
+            .map(|t| t.current())
+            == Some(Tag::RevokedSubRecord)
+    {
+        let sub_record =
+            SubDomainRecord::from_account_info(accounts.sub_record, Tag::RevokedSubRecord)?;
+        if Clock::get()?.unix_timestamp < sub_record.expiry_timestamp {
+            return Ok(Eligibility::RevokedNotExpired);
+        }
+    }
+
+    if let Some(collection) = registrar.nft_gated_collection.as_ref() {
+        let (nft_account, nft_metadata_account, nft_mint_record) = match (
+            accounts.nft_account,
+            accounts.nft_metadata_account,
+            accounts.nft_mint_record,
+        ) {
+            (Some(a), Some(b), Some(c)) => (a, b, c),
+            _ => return Ok(Eligibility::MustProvideNft),
+        };
+
+        if check_account_owner(nft_account, &spl_token::ID).is_err()
+            || check_account_owner(nft_metadata_account, &mpl_token_metadata::ID).is_err()
+        {
+            return Ok(Eligibility::InvalidNft);
+        }
+        let mint = match check_nft_holding_and_get_mint(nft_account, accounts.buyer.key) {
+            Ok(mint) => mint,
+            Err(_) => return Ok(Eligibility::InvalidNft),
+        };
+        if check_metadata(nft_metadata_account, collection).is_err()
+            || nft_metadata_account.key != &Metadata::find_pda(&mint).0
+        {
+            return Ok(Eligibility::InvalidNft);
+        }
+
+        let (pda, _) = MintRecord::find_key(&mint, accounts.registrar.key, program_id);
+        check_account_key(nft_mint_record, &pda)?;
+        if !nft_mint_record.data_is_empty() {
+            let mint_record = MintRecord::from_account_info(nft_mint_record, Tag::MintRecord)?;
+            if mint_record.count >= registrar.max_nft_mint {
+                return Ok(Eligibility::MintLimitReached);
+            }
+        }
+    }
+
+    Ok(Eligibility::Eligible)
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+    let config = Config::from_account_info_or_default(accounts.config, program_id)?;
+
+    let eligibility = if params.irrevocable && registrar.irrevocable_premium_bps == 0 {
+        Eligibility::IrrevocableNotOffered
+    } else {
+        get_eligibility(program_id, &accounts, &registrar, &params.domain)?
+    };
+
+    let quote = if matches!(
+        eligibility,
+        Eligibility::InvalidName | Eligibility::IrrevocableNotOffered
+    ) {
+        Quote {
+            price: 0,
+            registrar_proceeds: 0,
+            protocol_fee: 0,
+            eligibility,
+        }
+    } else {
+        // Same computation as in `register`
+        let price = get_registration_price(params.domain, &registrar, params.irrevocable)?;
+        let protocol_fee = get_protocol_fee(price, config.get_fee_bps(accounts.registrar.key))?;
+        Quote {
+            price,
+            registrar_proceeds: price
+                .checked_sub(protocol_fee)
+                .ok_or(SubRegisterError::Overflow)?,
+            protocol_fee,
+            eligibility,
+        }
+    };
+
+    set_return_data(&quote.try_to_vec()?);
+
+    Ok(())
+}
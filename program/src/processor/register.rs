@@ -8,11 +8,14 @@ use crate::{
     cpi::Cpi,
     error::SubRegisterError,
     state::{
-        mint_record::MintRecord, registry::Registrar, subdomain_record::SubDomainRecord, Tag,
-        FEE_ACC_OWNER, FEE_PCT, ROOT_DOMAIN_ACCOUNT,
+        config::Config, mint_record::MintRecord, registry::Registrar,
+        subdomain_record::SubDomainRecord, Tag,
     },
     utils,
-    utils::{check_metadata, check_nft_holding_and_get_mint, get_subdomain_reverse},
+    utils::{
+        check_metadata, check_nft_holding_and_get_mint, check_subdomain_name, find_account,
+        get_protocol_fee, get_subdomain_reverse_with_class,
+    },
 };
 
 use {
@@ -44,6 +47,12 @@ use {
 pub struct Params {
     /// The subdomain to register
     pub domain: String,
+    /// The owner of the subdomain, defaults to the fee payer
+    /// If the registrar is NFT gated, the recipient must hold the NFT
+    /// The recipient account must be appended to the accounts if it is not the fee payer
+    pub recipient: Option<Pubkey>,
+    /// Registers an irrevocable subdomain, priced with the irrevocable premium of the registrar
+    pub irrevocable: bool,
 }
 
 #[derive(InstructionsAccount)]
@@ -60,13 +69,13 @@ pub struct Accounts<'a, T> {
     /// The rent sysvar account
     pub rent_sysvar: &'a T,
 
-    /// The name auctioning program account
+    /// The program creating the reverse lookups of the TLD root
     pub sns_registrar_program: &'a T,
 
-    /// The .sol root domain
+    /// The TLD root domain of the registrar
     pub root_domain: &'a T,
 
-    /// The reverse lookup class accoutn
+    /// The reverse lookup class of the TLD root
     pub reverse_lookup_class: &'a T,
 
     /// The fee account of the registry
@@ -95,6 +104,9 @@ pub struct Accounts<'a, T> {
     #[cons(writable)]
     pub bonfida_fee_account: &'a T,
 
+    /// The global config account, may not be initialized, required before the subrecord
+    pub config: &'a T,
+
     #[cons(writable)]
     /// The subrecord account
     pub sub_record: &'a T,
@@ -132,6 +144,7 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             sub_reverse_account: next_account_info(accounts_iter)?,
             fee_payer: next_account_info(accounts_iter)?,
             bonfida_fee_account: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
             sub_record: next_account_info(accounts_iter)?,
             nft_account: next_account_info(accounts_iter).ok(),
             nft_metadata_account: next_account_info(accounts_iter).ok(),
@@ -143,12 +156,7 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
         check_account_key(accounts.spl_token_program, &spl_token::ID)?;
         check_account_key(accounts.spl_name_service, &spl_name_service::ID)?;
         check_account_key(accounts.rent_sysvar, &sysvar::rent::id())?;
-        check_account_key(accounts.sns_registrar_program, &sns_registrar::ID)?;
-        check_account_key(accounts.root_domain, &ROOT_DOMAIN_ACCOUNT)?;
-        check_account_key(
-            accounts.reverse_lookup_class,
-            &sns_registrar::central_state::KEY,
-        )?;
+        check_account_key(accounts.config, &Config::find_key(program_id).0)?;
 
         // Check owners
         check_account_owner(accounts.fee_account, &spl_token::ID)?;
@@ -170,28 +178,32 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
 }
 
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let account_infos = accounts;
     let accounts = Accounts::parse(accounts, program_id)?;
     let (subrecord_key, subrecord_nonce) =
         SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
     let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
 
+    if registrar.is_winding_down() {
+        return Err(SubRegisterError::RegistrarWindingDown.into());
+    }
+
     check_account_key(accounts.fee_account, &registrar.fee_account)?;
     check_account_key(accounts.parent_domain_account, &registrar.domain_account)?;
     check_account_key(accounts.sub_record, &subrecord_key)?;
+    registrar.root_domain.check_accounts(
+        accounts.root_domain,
+        accounts.sns_registrar_program,
+        accounts.reverse_lookup_class,
+    )?;
 
-    check_token_account_owner(accounts.bonfida_fee_account, &FEE_ACC_OWNER)?;
-
-    if !params.domain.starts_with('\x00') {
-        return Err(SubRegisterError::InvalidSubdomain.into());
-    }
+    let config = Config::from_account_info_or_default(accounts.config, program_id)?;
+    check_token_account_owner(accounts.bonfida_fee_account, &config.fee_recipient)?;
 
-    if params.domain.trim().to_lowercase() != params.domain {
-        return Err(SubRegisterError::InvalidSubdomain.into());
-    }
+    check_subdomain_name(&params.domain)?;
 
-    if params.domain.contains('.') {
-        return Err(SubRegisterError::InvalidSubdomain.into());
-    }
+    let recipient = params.recipient.unwrap_or(*accounts.fee_payer.key);
+    let recipient_account = find_account(account_infos, &recipient)?;
 
     // Handle NFT gated case first
     let mut mint_record_key: Option<Pubkey> = None;
@@ -210,7 +222,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
         check_account_owner(nft_account, &spl_token::ID).unwrap();
         check_account_owner(nft_metadata_account, &mpl_token_metadata::ID).unwrap();
 
-        let mint = check_nft_holding_and_get_mint(nft_account, accounts.fee_payer.key)?;
+        let mint = check_nft_holding_and_get_mint(nft_account, &recipient)?;
         check_metadata(nft_metadata_account, collection)?;
 
         // Check metadata PDA derivation
@@ -270,11 +282,9 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     check_account_key(accounts.sub_domain_account, &name_account_key)?;
 
     // Transfer fees
-    let price = utils::get_domain_price(params.domain.clone(), &registrar.price_schedule);
-    let fees = (price
-        .checked_mul(FEE_PCT)
-        .ok_or(SubRegisterError::Overflow)?)
-        / 100;
+    let price =
+        utils::get_registration_price(params.domain.clone(), &registrar, params.irrevocable)?;
+    let fees = get_protocol_fee(price, config.get_fee_bps(accounts.registrar.key))?;
     let price = price.checked_sub(fees).ok_or(SubRegisterError::Overflow)?;
     let ix = spl_token::instruction::transfer(
         &spl_token::ID,
@@ -323,7 +333,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
         },
         *accounts.sub_domain_account.key,
         *accounts.fee_payer.key,
-        *accounts.fee_payer.key,
+        recipient,
         None,
         Some(registrar.domain_account),
         Some(*accounts.registrar.key),
@@ -341,16 +351,17 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
             accounts.system_program.clone(),
             accounts.fee_payer.clone(),
             accounts.sub_domain_account.clone(),
-            accounts.fee_payer.clone(),
+            recipient_account.clone(),
             accounts.parent_domain_account.clone(),
             accounts.registrar.clone(),
         ],
         &[seeds],
     )?;
 
-    let expected_sub_reverse_key = get_subdomain_reverse(
+    let expected_sub_reverse_key = get_subdomain_reverse_with_class(
         params.domain.strip_prefix('\x00').unwrap(),
         accounts.parent_domain_account.key,
+        &registrar.root_domain.reverse_lookup_class,
     );
 
     if accounts.sub_reverse_account.key != &expected_sub_reverse_key {
@@ -360,13 +371,13 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
     // Sub reverse should be passed in the accounts and check if does not already exist
     if accounts.sub_reverse_account.data_is_empty() {
         let ix = create_reverse(
-            sns_registrar::ID,
+            registrar.root_domain.registrar_program,
             create_reverse::Accounts {
                 naming_service_program: &spl_name_service::ID,
-                root_domain: &ROOT_DOMAIN_ACCOUNT,
+                root_domain: &registrar.root_domain.name_account,
                 reverse_lookup: accounts.sub_reverse_account.key,
                 system_program: &system_program::ID,
-                central_state: &sns_registrar::central_state::KEY,
+                central_state: &registrar.root_domain.reverse_lookup_class,
                 fee_payer: accounts.fee_payer.key,
                 rent_sysvar: accounts.rent_sysvar.key,
                 parent_name: Some(accounts.parent_domain_account.key),
@@ -401,54 +412,64 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
         .borrow()
         .first()
         .and_then(|x| Tag::from_u8(*x))
+        .map(|t| t.current())
         .unwrap_or(Tag::Uninitialized);
 
-    // Create subrecord account
-    let sub_record = match (accounts.sub_record.owner, current_tag) {
+    // Keep track of the purchase for refunds, which are only owed when the vault received the
+    // proceeds
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let (vault_key, _) = Registrar::find_vault_key(accounts.registrar.key, program_id);
+    let mut sub_record = SubDomainRecord::new(
+        *accounts.registrar.key,
+        *accounts.sub_domain_account.key,
+        *accounts.fee_payer.key,
+    );
+    sub_record.mint_record = mint_record_key;
+    sub_record.registered_at = current_timestamp;
+    sub_record.price_paid = if registrar.fee_account == vault_key {
+        price
+    } else {
+        0
+    };
+    sub_record.bound_owner = registrar.soulbound.then_some(recipient);
+    sub_record.owner = recipient;
+    sub_record.last_owner_sync = current_timestamp;
+    sub_record.irrevocable = params.irrevocable;
+
+    // Create subrecord account, its size depends on the optional fields set above
+    match (accounts.sub_record.owner, current_tag) {
         (&system_program::ID, _) => {
-            let mut r = SubDomainRecord::new(
-                *accounts.registrar.key,
-                *accounts.sub_domain_account.key,
-                *accounts.fee_payer.key,
-            );
             let seeds: &[&[u8]] = &[
                 SubDomainRecord::SEEDS,
                 &accounts.sub_domain_account.key.to_bytes(),
                 &[subrecord_nonce],
             ];
-            r.mint_record = mint_record_key;
             Cpi::create_account(
                 program_id,
                 accounts.system_program,
                 accounts.fee_payer,
                 accounts.sub_record,
                 seeds,
-                r.borsh_len(),
+                sub_record.borsh_len(),
             )?;
-            r
         }
-        (k, Tag::RevokedSubRecord) if k == program_id => {
-            let mut r =
-                SubDomainRecord::from_account_info(accounts.sub_record, Tag::RevokedSubRecord)?;
-            let current_timestamp = Clock::get()?.unix_timestamp;
-            if current_timestamp < r.expiry_timestamp {
-                return Err(SubRegisterError::RevokedSubdomainNotExpired.into());
+        (k, Tag::RevokedSubRecord | Tag::ClosedSubRecord) if k == program_id => {
+            if current_tag == Tag::RevokedSubRecord {
+                let revoked =
+                    SubDomainRecord::from_account_info(accounts.sub_record, Tag::RevokedSubRecord)?;
+                if current_timestamp < revoked.expiry_timestamp {
+                    return Err(SubRegisterError::RevokedSubdomainNotExpired.into());
+                }
+                sub_record.allocator = revoked.allocator;
+            }
+
+            // Previous records can have been written with an older layout or other optional fields
+            if accounts.sub_record.data_len() != sub_record.borsh_len() {
+                accounts.sub_record.realloc(sub_record.borsh_len(), false)?;
             }
-            r.tag = Tag::SubRecord;
-            r.expiry_timestamp = i64::MAX;
-            r.mint_record = mint_record_key;
-            r
-        }
-        (k, Tag::ClosedSubRecord) if k == program_id => {
-            let mut r = SubDomainRecord::new(
-                *accounts.registrar.key,
-                *accounts.sub_domain_account.key,
-                *accounts.fee_payer.key,
-            );
-            r.mint_record = mint_record_key;
 
             let current_lamports = **accounts.sub_record.lamports.borrow();
-            let rent = Rent::get()?.minimum_balance(r.borsh_len());
+            let rent = Rent::get()?.minimum_balance(sub_record.borsh_len());
 
             if current_lamports < rent {
                 let ix = transfer(
@@ -465,11 +486,9 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) ->
                     ],
                 )?;
             }
-
-            r
         }
         _ => return Err(ProgramError::InvalidArgument),
-    };
+    }
 
     sub_record.save(&mut accounts.sub_record.data.borrow_mut());
 
@@ -0,0 +1,147 @@
+//! Allow the authority of a `Registrar` to grant, update or remove the permissions of a delegate
+//! Setting empty permissions closes the delegate account
+//! The permissions lapse when the registrar authority changes, the new authority must grant them again
+
+use crate::{
+    cpi::Cpi,
+    state::{
+        delegate::{Delegate, Permission},
+        registry::Registrar,
+        Tag,
+    },
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    enumflags2::BitFlags,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The key of the delegate
+    pub delegate: Pubkey,
+    /// The `Permission` flags granted to the delegate
+    pub permissions: u64,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The system program account
+    pub system_program: &'a T,
+
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable, signer)]
+    /// The registrar authority
+    pub authority: &'a T,
+
+    #[cons(writable)]
+    /// The delegate account
+    pub delegate_account: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            system_program: next_account_info(accounts_iter)?,
+            registrar: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            delegate_account: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.system_program, &system_program::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+
+    let (delegate_key, nonce) =
+        Delegate::find_key(accounts.registrar.key, &params.delegate, program_id);
+    check_account_key(accounts.delegate_account, &delegate_key)?;
+
+    let permissions = BitFlags::<Permission>::from_bits(params.permissions).map_err(|_| {
+        msg!("Invalid permissions");
+        ProgramError::InvalidArgument
+    })?;
+
+    if permissions.is_empty() {
+        // Close the delegate account
+        if accounts.delegate_account.data_is_empty() {
+            return Ok(());
+        }
+        check_account_owner(accounts.delegate_account, program_id)?;
+        let mut delegate = Delegate::from_account_info(accounts.delegate_account, Tag::Delegate)?;
+        delegate.tag = Tag::Uninitialized;
+        delegate.permissions = 0;
+        delegate.save(&mut accounts.delegate_account.data.borrow_mut());
+
+        let mut delegate_lamports = accounts.delegate_account.lamports.borrow_mut();
+        let mut authority_lamports = accounts.authority.lamports.borrow_mut();
+        **authority_lamports += **delegate_lamports;
+        **delegate_lamports = 0;
+
+        return Ok(());
+    }
+
+    let delegate = Delegate::new(
+        accounts.registrar.key,
+        accounts.authority.key,
+        &params.delegate,
+        permissions,
+    );
+
+    if accounts.delegate_account.data_is_empty() {
+        let seeds: &[&[u8]] = &[
+            Delegate::SEEDS,
+            &accounts.registrar.key.to_bytes(),
+            &params.delegate.to_bytes(),
+            &[nonce],
+        ];
+        Cpi::create_account(
+            program_id,
+            accounts.system_program,
+            accounts.authority,
+            accounts.delegate_account,
+            seeds,
+            delegate.borsh_len(),
+        )?;
+    } else {
+        check_account_owner(accounts.delegate_account, program_id)?;
+        Delegate::from_account_info(accounts.delegate_account, Tag::Delegate)?;
+    }
+
+    // Serialize state
+    delegate.save(&mut accounts.delegate_account.data.borrow_mut());
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Allow the authority of a `Registrar` to make a subdomain irrevocable
+//! This cannot be undone
+
+use crate::{
+    error::SubRegisterError,
+    state::{registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The subdomain record account
+    pub sub_record: &'a T,
+
+    #[cons(signer)]
+    /// The registrar authority
+    pub authority: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            registrar: next_account_info(accounts_iter)?,
+            sub_record: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.sub_record, program_id)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+    let mut sub_record = SubDomainRecord::from_account_info(accounts.sub_record, Tag::SubRecord)?;
+
+    if sub_record.is_stale(&registrar) {
+        return Err(SubRegisterError::StaleSubRecord.into());
+    }
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+    check_account_key(accounts.registrar, &sub_record.registrar)?;
+
+    sub_record.irrevocable = true;
+
+    // Serialize state
+    sub_record.save(&mut accounts.sub_record.data.borrow_mut());
+
+    Ok(())
+}
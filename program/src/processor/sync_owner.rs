@@ -0,0 +1,71 @@
+//! Record the current owner of a subdomain in its subrecord
+//! This allows registrars to enumerate their holders without reading the name accounts
+
+use crate::state::{subdomain_record::SubDomainRecord, Tag};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+    spl_name_service::state::NameRecordHeader,
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The subdomain account
+    pub sub_domain_account: &'a T,
+
+    #[cons(writable)]
+    /// The subrecord account
+    pub sub_record: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            sub_domain_account: next_account_info(accounts_iter)?,
+            sub_record: next_account_info(accounts_iter)?,
+        };
+
+        // Check owners
+        check_account_owner(accounts.sub_domain_account, &spl_name_service::ID)?;
+        check_account_owner(accounts.sub_record, program_id)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+
+    let mut sub_record = SubDomainRecord::from_account_info(accounts.sub_record, Tag::SubRecord)?;
+
+    let (subrecord_key, _) = SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
+    check_account_key(accounts.sub_record, &subrecord_key)?;
+
+    let header = NameRecordHeader::unpack_from_slice(&accounts.sub_domain_account.data.borrow())?;
+    sub_record.sync_owner(&header.owner, Clock::get()?.unix_timestamp)?;
+
+    // Serialize state
+    sub_record.save(&mut accounts.sub_record.data.borrow_mut());
+
+    Ok(())
+}
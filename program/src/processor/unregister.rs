@@ -3,22 +3,26 @@
 use crate::{
     error::SubRegisterError,
     state::{mint_record::MintRecord, registry::Registrar, subdomain_record::SubDomainRecord, Tag},
+    utils::{get_refund_amount, next_account_with_tag},
 };
 
 use {
     bonfida_utils::{
-        checks::{check_account_key, check_account_owner, check_signer},
+        checks::{check_account_key, check_account_owner, check_signer, check_token_account_owner},
         BorshSize, InstructionsAccount,
     },
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
-        program::invoke,
+        msg,
+        program::{invoke, invoke_signed},
         program_error::ProgramError,
         program_pack::Pack,
         pubkey::Pubkey,
         system_program,
+        sysvar::Sysvar,
     },
     spl_name_service::{instruction::delete, state::NameRecordHeader},
 };
@@ -52,6 +56,17 @@ pub struct Accounts<'a, T> {
 
     #[cons(writable)]
     pub mint_record: Option<&'a T>,
+
+    /// Optional SPL token program account if the subdomain is refundable
+    pub spl_token_program: Option<&'a T>,
+
+    #[cons(writable)]
+    /// Optional registrar vault if the subdomain is refundable
+    pub vault: Option<&'a T>,
+
+    #[cons(writable)]
+    /// Optional token account of the domain owner receiving the refund
+    pub refund_destination: Option<&'a T>,
 }
 
 impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
@@ -67,7 +82,10 @@ impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
             sub_domain_account: next_account_info(accounts_iter)?,
             sub_record: next_account_info(accounts_iter)?,
             domain_owner: next_account_info(accounts_iter)?,
-            mint_record: next_account_info(accounts_iter).ok(),
+            mint_record: next_account_with_tag(accounts_iter, program_id, Tag::MintRecord),
+            spl_token_program: next_account_info(accounts_iter).ok(),
+            vault: next_account_info(accounts_iter).ok(),
+            refund_destination: next_account_info(accounts_iter).ok(),
         };
 
         // Check keys
@@ -91,6 +109,10 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
     let mut registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
     let mut sub_record = SubDomainRecord::from_account_info(accounts.sub_record, Tag::SubRecord)?;
 
+    if sub_record.is_stale(&registrar) {
+        return Err(SubRegisterError::StaleSubRecord.into());
+    }
+
     let (subrecord_key, _) = SubDomainRecord::find_key(accounts.sub_domain_account.key, program_id);
     check_account_key(accounts.sub_record, &subrecord_key)?;
 
@@ -133,6 +155,67 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], _params: Params) -
         mint_record.save(&mut mint_record_account.data.borrow_mut());
     }
 
+    // Handle refund
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let refund = get_refund_amount(
+        &registrar.refund_policy,
+        sub_record.price_paid,
+        sub_record.registered_at,
+        current_timestamp,
+    )?;
+    if refund > 0 {
+        let spl_token_program = accounts
+            .spl_token_program
+            .ok_or(SubRegisterError::MissingAccount)?;
+        let vault = accounts.vault.ok_or(SubRegisterError::MissingAccount)?;
+        let refund_destination = accounts
+            .refund_destination
+            .ok_or(SubRegisterError::MissingAccount)?;
+
+        check_account_key(spl_token_program, &spl_token::ID)?;
+        let (vault_key, _) = Registrar::find_vault_key(accounts.registrar.key, program_id);
+        check_account_key(vault, &vault_key)?;
+        check_account_owner(vault, &spl_token::ID)?;
+        check_token_account_owner(refund_destination, accounts.domain_owner.key)?;
+
+        // The authority can withdraw from the vault, the refund is capped to its balance so that
+        // the subdomain can always be unregistered
+        let available = spl_token::state::Account::unpack(&vault.data.borrow())?.amount;
+        if available < refund {
+            msg!(
+                "[+] The vault holds {} tokens, {} were owed to the subdomain owner",
+                available,
+                refund
+            );
+        }
+        let refund = refund.min(available);
+        msg!("[+] Refunding {} tokens", refund);
+
+        let ix = spl_token::instruction::transfer(
+            &spl_token::ID,
+            vault.key,
+            refund_destination.key,
+            accounts.registrar.key,
+            &[],
+            refund,
+        )?;
+        let seeds: &[&[u8]] = &[
+            Registrar::SEEDS,
+            &registrar.domain_account.to_bytes(),
+            &[registrar.nonce],
+        ];
+        invoke_signed(
+            &ix,
+            &[
+                spl_token_program.clone(),
+                vault.clone(),
+                refund_destination.clone(),
+                accounts.registrar.clone(),
+            ],
+            &[seeds],
+        )?;
+    }
+
     // Close subrecord account
     sub_record.tag = Tag::ClosedSubRecord;
     sub_record.save(&mut accounts.sub_record.data.borrow_mut());
@@ -0,0 +1,109 @@
+//! Allow the authority of a `Registrar` to withdraw funds from the vault
+
+use crate::state::{registry::Registrar, Tag};
+
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner, check_signer},
+        BorshSize, InstructionsAccount,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        program::invoke_signed,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize)]
+pub struct Params {
+    /// The amount of tokens to withdraw
+    pub amount: u64,
+}
+
+#[derive(InstructionsAccount)]
+pub struct Accounts<'a, T> {
+    /// The SPL token program account
+    pub spl_token_program: &'a T,
+
+    /// The registrar account
+    pub registrar: &'a T,
+
+    #[cons(writable)]
+    /// The vault token account
+    pub vault: &'a T,
+
+    #[cons(writable)]
+    /// The destination token account
+    pub destination: &'a T,
+
+    #[cons(signer)]
+    /// The authority of the registrar
+    pub authority: &'a T,
+}
+
+impl<'a, 'b: 'a> Accounts<'a, AccountInfo<'b>> {
+    pub fn parse(
+        accounts: &'a [AccountInfo<'b>],
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let accounts = Accounts {
+            spl_token_program: next_account_info(accounts_iter)?,
+            registrar: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        // Check keys
+        check_account_key(accounts.spl_token_program, &spl_token::ID)?;
+
+        // Check owners
+        check_account_owner(accounts.registrar, program_id)?;
+        check_account_owner(accounts.vault, &spl_token::ID)?;
+        check_account_owner(accounts.destination, &spl_token::ID)?;
+
+        // Check signer
+        check_signer(accounts.authority)?;
+
+        Ok(accounts)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let accounts = Accounts::parse(accounts, program_id)?;
+    let registrar = Registrar::from_account_info(accounts.registrar, Tag::Registrar)?;
+
+    check_account_key(accounts.authority, &registrar.authority)?;
+    let (vault_key, _) = Registrar::find_vault_key(accounts.registrar.key, program_id);
+    check_account_key(accounts.vault, &vault_key)?;
+
+    let ix = spl_token::instruction::transfer(
+        &spl_token::ID,
+        accounts.vault.key,
+        accounts.destination.key,
+        accounts.registrar.key,
+        &[],
+        params.amount,
+    )?;
+    let seeds: &[&[u8]] = &[
+        Registrar::SEEDS,
+        &registrar.domain_account.to_bytes(),
+        &[registrar.nonce],
+    ];
+    invoke_signed(
+        &ix,
+        &[
+            accounts.spl_token_program.clone(),
+            accounts.vault.clone(),
+            accounts.destination.clone(),
+            accounts.registrar.clone(),
+        ],
+        &[seeds],
+    )?;
+
+    Ok(())
+}
@@ -31,6 +31,7 @@ pub fn revoke_unchecked<'a>(
     Cpi::transfer_subdomain(
         &registrar,
         registrar_account,
+        registrar_account.key,
         subdomain_account,
         parent_domain_account,
         name_class_account,
@@ -1,10 +1,18 @@
+use crate::error::SubRegisterError;
+
 use {
     bonfida_utils::BorshSize,
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::{pubkey, pubkey::Pubkey},
+    solana_program::{
+        account_info::AccountInfo, msg, program_error::ProgramError, pubkey, pubkey::Pubkey,
+    },
 };
 
+pub mod bonding_curve;
+pub mod config;
+pub mod delegate;
 pub mod mint_record;
+pub mod pending_change;
 pub mod registry;
 pub mod schedule;
 pub mod subdomain_record;
@@ -16,15 +24,31 @@ pub const FEE_PCT: u64 = 5;
 // Fee account
 pub const FEE_ACC_OWNER: Pubkey = pubkey!("5D2zKog251d6KPCyFyLMt3KroWwXXPWSgTPyhV22K2gR");
 
-#[derive(BorshSerialize, BorshDeserialize, BorshSize, PartialEq, Debug, Eq)]
+// Fee used when the config account does not exist, in basis points
+pub const DEFAULT_FEE_BPS: u64 = FEE_PCT * 100;
+// Fees cannot exceed 100%
+pub const MAX_FEE_BPS: u64 = 10_000;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
 #[allow(missing_docs)]
 pub enum Tag {
     Uninitialized,
-    Registrar,
+    // Registrar created before versioned layouts, see `Migrate`
+    LegacyRegistrar,
     ClosedRegistrar,
+    // Subdomain records created before versioned layouts, see `Migrate`
+    LegacySubRecord,
+    LegacyClosedSubRecord,
+    // Mint record created before versioned layouts, see `Migrate`
+    LegacyMintRecord,
+    LegacyRevokedSubRecord,
+    Config,
+    Delegate,
+    PendingChange,
+    Registrar,
+    MintRecord,
     SubRecord,
     ClosedSubRecord,
-    MintRecord,
     RevokedSubRecord,
 }
 
@@ -38,13 +62,83 @@ impl Tag {
     pub fn from_u8(value: u8) -> Option<Self> {
         match value {
             0 => Some(Self::Uninitialized),
-            1 => Some(Self::Registrar),
+            1 => Some(Self::LegacyRegistrar),
             2 => Some(Self::ClosedRegistrar),
-            3 => Some(Self::SubRecord),
-            4 => Some(Self::ClosedSubRecord),
-            5 => Some(Self::MintRecord),
-            6 => Some(Self::RevokedSubRecord),
+            3 => Some(Self::LegacySubRecord),
+            4 => Some(Self::LegacyClosedSubRecord),
+            5 => Some(Self::LegacyMintRecord),
+            6 => Some(Self::LegacyRevokedSubRecord),
+            7 => Some(Self::Config),
+            8 => Some(Self::Delegate),
+            9 => Some(Self::PendingChange),
+            10 => Some(Self::Registrar),
+            11 => Some(Self::MintRecord),
+            12 => Some(Self::SubRecord),
+            13 => Some(Self::ClosedSubRecord),
+            14 => Some(Self::RevokedSubRecord),
             _ => None,
         }
     }
+
+    // The tag used by the accounts of the same type with a versioned layout
+    pub fn current(&self) -> Self {
+        match self {
+            Self::LegacyRegistrar => Self::Registrar,
+            Self::LegacyMintRecord => Self::MintRecord,
+            Self::LegacySubRecord => Self::SubRecord,
+            Self::LegacyClosedSubRecord => Self::ClosedSubRecord,
+            Self::LegacyRevokedSubRecord => Self::RevokedSubRecord,
+            t => *t,
+        }
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.current() != *self
+    }
+}
+
+// Accounts with a versioned layout store their version right after the `Tag`
+// Accounts created before versioned layouts are considered to be version 0, they can still be
+// read and are converted to the current layout with `Migrate`
+// A new layout requires a version bump along with the conversion from the layout it replaces
+pub trait Versioned: BorshSerialize + BorshDeserialize + BorshSize {
+    // The current layout version
+    const VERSION: u8;
+
+    // Converts the data of an account using the layout `version`, older than `VERSION`, to the
+    // current layout
+    // Each layout only converts from the one it replaces: once version 2 is introduced, version 1
+    // data is read with the version 1 layout, older data goes through the version 1 `upgrade`
+    fn upgrade(version: u8, data: &[u8]) -> Result<Self, ProgramError>;
+
+    // The version of the layout used by the account data
+    fn version_of(data: &[u8]) -> u8 {
+        match Tag::from_u8(data[0]) {
+            Some(t) if t.is_legacy() => 0,
+            _ => data.get(1).copied().unwrap_or_default(),
+        }
+    }
+
+    // Reads the account data whatever its version, the tag must be checked by the caller
+    // Outdated accounts can only be written to once migrated as their data would not fit
+    fn deserialize_versioned(a: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = &a.data.borrow() as &[u8];
+        let version = Self::version_of(data);
+        if data[0] == Tag::Uninitialized as u8 || version == Self::VERSION {
+            return Ok(Self::deserialize(&mut &data[..])?);
+        }
+        if version > Self::VERSION {
+            return Err(unsupported_version(version));
+        }
+        if a.is_writable {
+            return Err(SubRegisterError::AccountNeedsMigration.into());
+        }
+        Self::upgrade(version, data)
+    }
+}
+
+// Returned by `Versioned::upgrade` for the versions it cannot convert from
+pub fn unsupported_version(version: u8) -> ProgramError {
+    msg!("Unsupported account version {}", version);
+    ProgramError::InvalidAccountData
 }
@@ -0,0 +1,169 @@
+use crate::error::SubRegisterError;
+use {
+    bonfida_utils::BorshSize,
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::program_error::ProgramError,
+    std::convert::TryFrom,
+};
+
+// A multiplier of 1x in basis points
+pub const MULTIPLIER_BASE_BPS: u64 = 10_000;
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
+pub enum CurveKind {
+    // The schedule price is used as is
+    None,
+    // The multiplier increases by `rate_bps` for every active subdomain
+    Linear,
+    // The multiplier increases by `rate_bps` every `step` active subdomains
+    Stepped,
+    // The multiplier compounds by `rate_bps` every `step` active subdomains
+    Exponential,
+}
+
+impl Default for CurveKind {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+// Scales the schedule price with the number of active subdomains of a registrar
+#[derive(
+    BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Default, Clone, Copy,
+)]
+pub struct BondingCurve {
+    pub kind: CurveKind,
+    // The increase of the multiplier in basis points
+    pub rate_bps: u64,
+    // The number of subdomains between two increases (stepped and exponential curves)
+    pub step: u64,
+    // The maximum multiplier in basis points
+    pub max_multiplier_bps: u64,
+}
+
+impl BondingCurve {
+    pub fn is_valid(&self) -> bool {
+        match self.kind {
+            CurveKind::None => true,
+            CurveKind::Linear => self.max_multiplier_bps >= MULTIPLIER_BASE_BPS,
+            CurveKind::Stepped | CurveKind::Exponential => {
+                self.step > 0 && self.max_multiplier_bps >= MULTIPLIER_BASE_BPS
+            }
+        }
+    }
+
+    // Returns the price multiplier in basis points for `active_subs` registered subdomains
+    pub fn get_multiplier_bps(&self, active_subs: u64) -> u64 {
+        let base = MULTIPLIER_BASE_BPS as u128;
+        let cap = self.max_multiplier_bps as u128;
+        let multiplier = match self.kind {
+            CurveKind::None => return MULTIPLIER_BASE_BPS,
+            CurveKind::Linear => base + self.rate_bps as u128 * active_subs as u128,
+            CurveKind::Stepped => base + self.rate_bps as u128 * (active_subs / self.step) as u128,
+            CurveKind::Exponential => {
+                // Exponentiation by squaring, every intermediate value is capped
+                let mut result = base;
+                let mut factor = (base + self.rate_bps as u128).min(cap.max(base));
+                let mut exp = active_subs / self.step;
+                while exp > 0 && result < cap {
+                    if exp & 1 == 1 {
+                        result = (result * factor / base).min(cap);
+                    }
+                    factor = (factor * factor / base).min(cap);
+                    exp >>= 1;
+                }
+                result
+            }
+        };
+        multiplier.min(cap) as u64
+    }
+
+    // Applies the curve to a schedule price, rounded down
+    pub fn apply(&self, price: u64, active_subs: u64) -> Result<u64, ProgramError> {
+        let multiplier = self.get_multiplier_bps(active_subs) as u128;
+        let price = price as u128 * multiplier / MULTIPLIER_BASE_BPS as u128;
+        u64::try_from(price).map_err(|_| SubRegisterError::Overflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bonding_curve() {
+        let curve = BondingCurve::default();
+        assert!(curve.is_valid());
+        assert_eq!(curve.apply(1_000, 0).unwrap(), 1_000);
+        assert_eq!(curve.apply(1_000, u64::MAX).unwrap(), 1_000);
+
+        // +1% per sub up to 2x
+        let curve = BondingCurve {
+            kind: CurveKind::Linear,
+            rate_bps: 100,
+            step: 0,
+            max_multiplier_bps: 20_000,
+        };
+        assert!(curve.is_valid());
+        assert_eq!(curve.apply(1_000, 0).unwrap(), 1_000);
+        assert_eq!(curve.apply(1_000, 1).unwrap(), 1_010);
+        assert_eq!(curve.apply(1_000, 50).unwrap(), 1_500);
+        assert_eq!(curve.apply(1_000, 100).unwrap(), 2_000);
+        assert_eq!(curve.apply(1_000, u64::MAX).unwrap(), 2_000);
+
+        // +50% every 10 subs up to 3x
+        let curve = BondingCurve {
+            kind: CurveKind::Stepped,
+            rate_bps: 5_000,
+            step: 10,
+            max_multiplier_bps: 30_000,
+        };
+        assert!(curve.is_valid());
+        assert_eq!(curve.apply(1_000, 9).unwrap(), 1_000);
+        assert_eq!(curve.apply(1_000, 10).unwrap(), 1_500);
+        assert_eq!(curve.apply(1_000, 25).unwrap(), 2_000);
+        assert_eq!(curve.apply(1_000, 1_000).unwrap(), 3_000);
+
+        // x2 every 5 subs up to 10x
+        let curve = BondingCurve {
+            kind: CurveKind::Exponential,
+            rate_bps: 10_000,
+            step: 5,
+            max_multiplier_bps: 100_000,
+        };
+        assert!(curve.is_valid());
+        assert_eq!(curve.apply(1_000, 4).unwrap(), 1_000);
+        assert_eq!(curve.apply(1_000, 5).unwrap(), 2_000);
+        assert_eq!(curve.apply(1_000, 15).unwrap(), 8_000);
+        assert_eq!(curve.apply(1_000, 20).unwrap(), 10_000);
+        assert_eq!(curve.apply(1_000, u64::MAX).unwrap(), 10_000);
+
+        // +10% compounded every sub
+        let curve = BondingCurve {
+            kind: CurveKind::Exponential,
+            rate_bps: 1_000,
+            step: 1,
+            max_multiplier_bps: u64::MAX,
+        };
+        assert_eq!(curve.get_multiplier_bps(2), 12_100);
+        assert_eq!(curve.get_multiplier_bps(3), 13_310);
+        assert_eq!(curve.get_multiplier_bps(u64::MAX), u64::MAX);
+        assert!(curve.apply(u64::MAX, 1).is_err());
+
+        // Invalid curves
+        let curve = BondingCurve {
+            kind: CurveKind::Stepped,
+            rate_bps: 100,
+            step: 0,
+            max_multiplier_bps: 20_000,
+        };
+        assert!(!curve.is_valid());
+        let curve = BondingCurve {
+            kind: CurveKind::Linear,
+            rate_bps: 100,
+            step: 0,
+            max_multiplier_bps: 0,
+        };
+        assert!(!curve.is_valid());
+    }
+}
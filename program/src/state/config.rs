@@ -0,0 +1,210 @@
+use super::{
+    unsupported_version, Tag, Versioned, DEFAULT_FEE_BPS, FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
+};
+use crate::error::SubRegisterError;
+use {
+    bonfida_utils::{
+        checks::{check_account_key, check_account_owner},
+        BorshSize,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+// Protocol fee applied to a specific registrar instead of the global one
+#[derive(BorshSerialize, BorshDeserialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
+pub struct FeeOverride {
+    // The registrar the fee applies to
+    pub registrar: Pubkey,
+    // The protocol fee in basis points
+    pub fee_bps: u64,
+}
+
+// A TLD root under which registrars can be created
+#[derive(BorshSerialize, BorshDeserialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
+pub struct RootDomain {
+    // The name account of the TLD
+    pub name_account: Pubkey,
+    // The program creating the reverse lookups of the TLD, it must expose the `create_reverse`
+    // instruction of the SNS registrar
+    pub registrar_program: Pubkey,
+    // The class of the reverse lookups of the TLD
+    pub reverse_lookup_class: Pubkey,
+}
+
+// The .sol root, which is always allowed
+impl Default for RootDomain {
+    fn default() -> Self {
+        Self {
+            name_account: ROOT_DOMAIN_ACCOUNT,
+            registrar_program: sns_registrar::ID,
+            reverse_lookup_class: sns_registrar::central_state::KEY,
+        }
+    }
+}
+
+impl RootDomain {
+    // Checks the accounts passed to create the reverse lookups of the TLD
+    pub fn check_accounts(
+        &self,
+        name_account: &AccountInfo,
+        registrar_program: &AccountInfo,
+        reverse_lookup_class: &AccountInfo,
+    ) -> ProgramResult {
+        check_account_key(name_account, &self.name_account)?;
+        check_account_key(registrar_program, &self.registrar_program)?;
+        check_account_key(reverse_lookup_class, &self.reverse_lookup_class)?;
+        Ok(())
+    }
+}
+
+// The Config is a global account holding the protocol fee parameters
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
+pub struct Config {
+    pub tag: Tag,
+    pub version: u8,
+    pub nonce: u8,
+    // The admin authority of the config
+    pub admin: Pubkey,
+    // The protocol fee in basis points
+    pub fee_bps: u64,
+    // The owner of the token accounts receiving the protocol fees
+    pub fee_recipient: Pubkey,
+    // Registrar specific protocol fees
+    pub fee_overrides: Vec<FeeOverride>,
+    // The TLD roots under which registrars can be created in addition to .sol
+    pub root_domains: Vec<RootDomain>,
+}
+
+// Used when the config account has not been created yet
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tag: Tag::Uninitialized,
+            version: Self::VERSION,
+            nonce: 0,
+            admin: FEE_ACC_OWNER,
+            fee_bps: DEFAULT_FEE_BPS,
+            fee_recipient: FEE_ACC_OWNER,
+            fee_overrides: vec![],
+            root_domains: vec![],
+        }
+    }
+}
+
+impl Config {
+    pub const SEEDS: &'static [u8; 6] = b"config";
+
+    pub fn new(admin: &Pubkey, fee_bps: u64, fee_recipient: &Pubkey, nonce: u8) -> Self {
+        Self {
+            tag: Tag::Config,
+            version: Self::VERSION,
+            nonce,
+            admin: *admin,
+            fee_bps,
+            fee_recipient: *fee_recipient,
+            fee_overrides: vec![],
+            root_domains: vec![],
+        }
+    }
+
+    pub fn find_key(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Config::SEEDS], program_id)
+    }
+
+    pub fn save(&self, mut dst: &mut [u8]) {
+        self.serialize(&mut dst).unwrap()
+    }
+
+    pub fn from_account_info(a: &AccountInfo, tag: super::Tag) -> Result<Config, ProgramError> {
+        let account_tag = a.data.borrow()[0];
+        if account_tag != tag as u8 && account_tag != super::Tag::Uninitialized as u8 {
+            return Err(SubRegisterError::DataTypeMismatch.into());
+        }
+        Self::deserialize_versioned(a)
+    }
+
+    // Falls back to the default fee parameters when the config account does not exist
+    // The account key must be checked by the caller
+    pub fn from_account_info_or_default(
+        a: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<Config, ProgramError> {
+        if a.data_is_empty() {
+            return Ok(Config::default());
+        }
+        check_account_owner(a, program_id)?;
+        Config::from_account_info(a, Tag::Config)
+    }
+
+    pub fn get_fee_bps(&self, registrar: &Pubkey) -> u64 {
+        self.fee_overrides
+            .iter()
+            .find(|o| o.registrar == *registrar)
+            .map(|o| o.fee_bps)
+            .unwrap_or(self.fee_bps)
+    }
+
+    pub fn get_root_domain(&self, name_account: &Pubkey) -> Option<RootDomain> {
+        if *name_account == ROOT_DOMAIN_ACCOUNT {
+            return Some(RootDomain::default());
+        }
+        self.root_domains
+            .iter()
+            .find(|r| r.name_account == *name_account)
+            .copied()
+    }
+}
+
+impl Versioned for Config {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, _data: &[u8]) -> Result<Self, ProgramError> {
+        Err(unsupported_version(version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_fee_bps() {
+        let registrar = Pubkey::new_unique();
+        let mut config = Config::default();
+        assert_eq!(config.get_fee_bps(&registrar), DEFAULT_FEE_BPS);
+
+        config.fee_overrides.push(FeeOverride {
+            registrar,
+            fee_bps: 100,
+        });
+        assert_eq!(config.get_fee_bps(&registrar), 100);
+        assert_eq!(config.get_fee_bps(&Pubkey::new_unique()), DEFAULT_FEE_BPS);
+    }
+
+    #[test]
+    fn test_get_root_domain() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.get_root_domain(&ROOT_DOMAIN_ACCOUNT),
+            Some(RootDomain::default())
+        );
+
+        let root_domain = RootDomain {
+            name_account: Pubkey::new_unique(),
+            registrar_program: Pubkey::new_unique(),
+            reverse_lookup_class: Pubkey::new_unique(),
+        };
+        assert_eq!(config.get_root_domain(&root_domain.name_account), None);
+
+        config.root_domains.push(root_domain);
+        assert_eq!(
+            config.get_root_domain(&root_domain.name_account),
+            Some(root_domain)
+        );
+        assert_eq!(config.get_root_domain(&Pubkey::new_unique()), None);
+    }
+}
@@ -0,0 +1,89 @@
+use super::{unsupported_version, Tag, Versioned};
+use crate::error::SubRegisterError;
+use {
+    bonfida_utils::BorshSize,
+    borsh::{BorshDeserialize, BorshSerialize},
+    enumflags2::{bitflags, BitFlags},
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+};
+
+// The actions a delegate can perform on behalf of the registrar authority
+#[bitflags]
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    // Register subdomains without payment
+    AdminRegister = 1 << 0,
+    // Revoke subdomains
+    Revoke = 1 << 1,
+    // Transfer subdomains to a new owner
+    Transfer = 1 << 2,
+    // Edit the price schedule, the bonding curve and the irrevocable premium
+    EditPricing = 1 << 3,
+}
+
+// Delegates are used to give a scoped subset of the registrar authority to another key
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
+pub struct Delegate {
+    pub tag: Tag,
+    pub version: u8,
+    // The registrar the delegate acts on
+    pub registrar: Pubkey,
+    // The registrar authority which granted the permissions, they lapse once the authority changes
+    pub authority: Pubkey,
+    // The key allowed to sign on behalf of the registrar authority
+    pub delegate: Pubkey,
+    // The `Permission` flags of the delegate
+    pub permissions: u64,
+}
+
+impl Delegate {
+    pub const SEEDS: &'static [u8; 8] = b"delegate";
+
+    pub fn new(
+        registrar: &Pubkey,
+        authority: &Pubkey,
+        delegate: &Pubkey,
+        permissions: BitFlags<Permission>,
+    ) -> Self {
+        Self {
+            tag: Tag::Delegate,
+            version: Self::VERSION,
+            registrar: *registrar,
+            authority: *authority,
+            delegate: *delegate,
+            permissions: permissions.bits(),
+        }
+    }
+
+    pub fn find_key(registrar: &Pubkey, delegate: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Delegate::SEEDS, &registrar.to_bytes(), &delegate.to_bytes()],
+            program_id,
+        )
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        BitFlags::<Permission>::from_bits_truncate(self.permissions).contains(permission)
+    }
+
+    pub fn save(&self, mut dst: &mut [u8]) {
+        self.serialize(&mut dst).unwrap()
+    }
+
+    pub fn from_account_info(a: &AccountInfo, tag: super::Tag) -> Result<Delegate, ProgramError> {
+        let account_tag = a.data.borrow()[0];
+        if account_tag != tag as u8 && account_tag != super::Tag::Uninitialized as u8 {
+            return Err(SubRegisterError::DataTypeMismatch.into());
+        }
+        Self::deserialize_versioned(a)
+    }
+}
+
+impl Versioned for Delegate {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, _data: &[u8]) -> Result<Self, ProgramError> {
+        Err(unsupported_version(version))
+    }
+}
@@ -1,4 +1,4 @@
-use super::Tag;
+use super::{unsupported_version, Tag, Versioned};
 use crate::error::SubRegisterError;
 use {
     bonfida_utils::BorshSize,
@@ -10,18 +10,40 @@ use {
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
 pub struct MintRecord {
     pub tag: Tag,
+    // The layout version of the account
+    pub version: u8,
     // How many subdomains have been minted so far for this NFT
-    pub count: u8,
+    pub count: u32,
     // The mint of the NFT
     pub mint: Pubkey,
 }
 
+// The layout of the mint records created before versioned layouts
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
+pub struct LegacyMintRecord {
+    pub tag: Tag,
+    pub count: u8,
+    pub mint: Pubkey,
+}
+
+impl From<LegacyMintRecord> for MintRecord {
+    fn from(legacy: LegacyMintRecord) -> Self {
+        Self {
+            tag: Tag::MintRecord,
+            version: MintRecord::VERSION,
+            count: legacy.count as u32,
+            mint: legacy.mint,
+        }
+    }
+}
+
 impl MintRecord {
     pub const SEEDS: &'static [u8; 15] = b"nft_mint_record";
 
     pub fn new(mint: &Pubkey) -> Self {
         Self {
             tag: Tag::MintRecord,
+            version: Self::VERSION,
             count: 0,
             mint: *mint,
         }
@@ -39,11 +61,23 @@ impl MintRecord {
     }
 
     pub fn from_account_info(a: &AccountInfo, tag: super::Tag) -> Result<MintRecord, ProgramError> {
-        let mut data = &a.data.borrow() as &[u8];
-        if data[0] != tag as u8 && data[0] != super::Tag::Uninitialized as u8 {
+        let account_tag = a.data.borrow()[0];
+        if super::Tag::from_u8(account_tag).map(|t| t.current()) != Some(tag)
+            && account_tag != super::Tag::Uninitialized as u8
+        {
             return Err(SubRegisterError::DataTypeMismatch.into());
         }
-        let result = MintRecord::deserialize(&mut data)?;
-        Ok(result)
+        Self::deserialize_versioned(a)
+    }
+}
+
+impl Versioned for MintRecord {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, data: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            0 => Ok(LegacyMintRecord::deserialize(&mut &data[..])?.into()),
+            _ => Err(unsupported_version(version)),
+        }
     }
 }
@@ -0,0 +1,137 @@
+use super::{
+    bonding_curve::BondingCurve,
+    registry::{NftGatePolicy, RefundPolicy, Registrar},
+    schedule::Schedule,
+    unsupported_version, Tag, Versioned,
+};
+use crate::error::SubRegisterError;
+use {
+    bonfida_utils::BorshSize,
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+};
+
+// The registrar edits that are subject to the timelock
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize, Default)]
+pub struct RegistrarChange {
+    pub new_mint: Option<Pubkey>,
+    pub new_fee_account: Option<Pubkey>,
+    // Only schedules increasing the price of some names are timelocked
+    pub new_price_schedule: Option<Schedule>,
+    pub new_bonding_curve: Option<BondingCurve>,
+    // Only enabling soulbound subdomains is timelocked
+    pub new_soulbound: Option<bool>,
+    // Only shortening the timelock is timelocked
+    pub new_timelock_delay: Option<i64>,
+    pub new_nft_gated_collection: Option<Option<Pubkey>>,
+    // Only making subdomains revocable is timelocked
+    pub new_nft_gate_policy: Option<NftGatePolicy>,
+    // Only policies refunding less than the current one are timelocked
+    pub new_refund_policy: Option<RefundPolicy>,
+}
+
+impl RegistrarChange {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn apply(self, registrar: &mut Registrar) {
+        if let Some(new_mint) = self.new_mint {
+            registrar.mint = new_mint;
+        }
+        if let Some(new_fee_account) = self.new_fee_account {
+            registrar.fee_account = new_fee_account;
+        }
+        if let Some(new_price_schedule) = self.new_price_schedule {
+            registrar.price_schedule = new_price_schedule;
+        }
+        if let Some(new_bonding_curve) = self.new_bonding_curve {
+            registrar.bonding_curve = new_bonding_curve;
+        }
+        if let Some(new_soulbound) = self.new_soulbound {
+            registrar.soulbound = new_soulbound;
+        }
+        if let Some(new_timelock_delay) = self.new_timelock_delay {
+            registrar.timelock_delay = new_timelock_delay;
+        }
+        if let Some(new_nft_gated_collection) = self.new_nft_gated_collection {
+            registrar.nft_gated_collection = new_nft_gated_collection;
+        }
+        if let Some(new_nft_gate_policy) = self.new_nft_gate_policy {
+            registrar.nft_gate_policy = new_nft_gate_policy;
+        }
+        if let Some(new_refund_policy) = self.new_refund_policy {
+            registrar.refund_policy = new_refund_policy;
+        }
+    }
+}
+
+// A timelocked registrar edit, executable by anyone once `execution_timestamp` is reached
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
+pub struct PendingChange {
+    pub tag: Tag,
+    pub version: u8,
+    // The registrar to edit
+    pub registrar: Pubkey,
+    // The account which paid for the rent, refunded when the change is executed or cancelled
+    pub payer: Pubkey,
+    // The timestamp from which the change can be executed
+    pub execution_timestamp: i64,
+    pub change: RegistrarChange,
+}
+
+impl PendingChange {
+    pub const SEEDS: &'static [u8; 14] = b"pending_change";
+
+    pub fn new(
+        registrar: &Pubkey,
+        payer: &Pubkey,
+        execution_timestamp: i64,
+        change: RegistrarChange,
+    ) -> Self {
+        Self {
+            tag: Tag::PendingChange,
+            version: Self::VERSION,
+            registrar: *registrar,
+            payer: *payer,
+            execution_timestamp,
+            change,
+        }
+    }
+
+    pub fn find_key(registrar: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PendingChange::SEEDS, &registrar.to_bytes()], program_id)
+    }
+
+    pub fn save(&self, mut dst: &mut [u8]) {
+        self.serialize(&mut dst).unwrap()
+    }
+
+    pub fn from_account_info(
+        a: &AccountInfo,
+        tag: super::Tag,
+    ) -> Result<PendingChange, ProgramError> {
+        let account_tag = a.data.borrow()[0];
+        if account_tag != tag as u8 && account_tag != super::Tag::Uninitialized as u8 {
+            return Err(SubRegisterError::DataTypeMismatch.into());
+        }
+        Self::deserialize_versioned(a)
+    }
+
+    // Closes the account, the rent is sent to `lamports_target`
+    pub fn close(a: &AccountInfo, lamports_target: &AccountInfo) {
+        a.data.borrow_mut()[0] = Tag::Uninitialized as u8;
+        let mut account_lamports = a.lamports.borrow_mut();
+        let mut target_lamports = lamports_target.lamports.borrow_mut();
+        **target_lamports += **account_lamports;
+        **account_lamports = 0;
+    }
+}
+
+impl Versioned for PendingChange {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, _data: &[u8]) -> Result<Self, ProgramError> {
+        Err(unsupported_version(version))
+    }
+}
@@ -1,6 +1,8 @@
 use crate::error::SubRegisterError;
 
-use super::schedule;
+use super::{
+    bonding_curve::BondingCurve, config::RootDomain, schedule, unsupported_version, Versioned,
+};
 
 use {
     bonfida_utils::BorshSize,
@@ -8,9 +10,71 @@ use {
     solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
 };
 
+// Subdomain owners get at least 30 days of notice before a wind-down deadline
+pub const WIND_DOWN_DELAY_SECONDS_MIN: i64 = 2_592_000;
+
+// How the purchase price of a subdomain can be refunded when it is unregistered
+#[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
+pub enum RefundKind {
+    // Registrations are not refundable
+    None,
+    // The full price is refunded if the subdomain is unregistered within the window
+    Full,
+    // The unused fraction of the window is refunded
+    ProRata,
+}
+
+impl Default for RefundKind {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Default, Clone, Copy,
+)]
+pub struct RefundPolicy {
+    pub kind: RefundKind,
+    // The refund window in seconds, starting at the registration
+    pub window: i64,
+}
+
+impl RefundPolicy {
+    // Whether no subdomain is refunded less under `self` than under `other`
+    pub fn covers(&self, other: &RefundPolicy) -> bool {
+        // A pro rata refund never exceeds the full price
+        let rank = |kind: RefundKind| match kind {
+            RefundKind::None => 0,
+            RefundKind::ProRata => 1,
+            RefundKind::Full => 2,
+        };
+        other.kind == RefundKind::None
+            || other.window <= 0
+            || (rank(self.kind) >= rank(other.kind) && self.window >= other.window)
+    }
+}
+
+// What happens to the subdomains minted with an NFT which no longer belongs to the gated collection
+// i.e after the collection was replaced or removed with `EditRegistrar`
+#[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Clone, Copy)]
+pub enum NftGatePolicy {
+    // The subdomains are kept, the NFT holder can still reclaim them with `NftOwnerRevoke`
+    Grandfathered,
+    // The subdomains can be revoked by anyone with `NftOwnerRevoke`
+    Revocable,
+}
+
+impl Default for NftGatePolicy {
+    fn default() -> Self {
+        Self::Grandfathered
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq, Default)]
 pub struct Registrar {
     pub tag: super::Tag,
+    // The layout version of the account
+    pub version: u8,
     pub nonce: u8,
     // The admin authority of the registrar
     pub authority: Pubkey,
@@ -25,17 +89,77 @@ pub struct Registrar {
     // Optional: Whether to gate the registrations behind an NFT collection
     pub nft_gated_collection: Option<Pubkey>,
     // If the registration is gated behind an NFT collection, how many subdomains can be minted for 1 NFT
-    pub max_nft_mint: u8,
+    pub max_nft_mint: u32,
     // Whether to allow the admin authority to revoke subdomains
     pub allow_revoke: bool,
     // The price schedule for registrations (length based)
     pub price_schedule: schedule::Schedule,
     // The delay between a subdomain being revoked and it being ready for registration
     pub revoke_expiry_time: i64,
+    // Refunds are paid from the registrar vault on unregistration, the vault must be the fee account
+    pub refund_policy: RefundPolicy,
+    // Scales the schedule price with the number of active subdomains
+    pub bonding_curve: BondingCurve,
+    // Whether subdomains are bound to the wallet they are registered to
+    // A transferred soulbound subdomain can be revoked by anyone
+    pub soulbound: bool,
+    // The authority proposed with `ProposeAuthority`, waiting to be accepted
+    pub pending_authority: Option<Pubkey>,
+    // The delay in seconds before sensitive edits take effect, 0 if they are immediate
+    pub timelock_delay: i64,
+    // The price multiplier in basis points to register an irrevocable subdomain, 0 if not offered
+    pub irrevocable_premium_bps: u64,
+    // The policy applied to subdomains minted with NFTs outside of the gated collection
+    pub nft_gate_policy: NftGatePolicy,
+    // Set by `WindDown`: registrations are stopped and from this timestamp on the registrar can be
+    // closed with live subdomains, whose records can then be deleted by anyone
+    pub wind_down_deadline: Option<i64>,
+    // The creation timestamp, subdomain records registered before were created by a previous
+    // registrar at the same address, see `MigrateRegistrar`
+    pub created_at: i64,
+    // The TLD root of the domain, used to create the reverse lookups of the subdomains
+    pub root_domain: RootDomain,
+}
+
+// The layout of the registrars created before versioned layouts
+#[derive(BorshDeserialize, BorshSerialize, BorshSize, PartialEq, Debug, Eq)]
+pub struct LegacyRegistrar {
+    pub tag: super::Tag,
+    pub nonce: u8,
+    pub authority: Pubkey,
+    pub fee_account: Pubkey,
+    pub mint: Pubkey,
+    pub domain_account: Pubkey,
+    pub total_sub_created: u64,
+    pub nft_gated_collection: Option<Pubkey>,
+    pub max_nft_mint: u8,
+    pub allow_revoke: bool,
+    pub price_schedule: schedule::Schedule,
+    pub revoke_expiry_time: i64,
+}
+
+impl From<LegacyRegistrar> for Registrar {
+    fn from(legacy: LegacyRegistrar) -> Self {
+        let mut registrar = Registrar::new(
+            &legacy.authority,
+            &legacy.fee_account,
+            &legacy.mint,
+            &legacy.domain_account,
+            legacy.price_schedule,
+            legacy.nonce,
+            legacy.nft_gated_collection,
+            legacy.max_nft_mint as u32,
+            legacy.allow_revoke,
+            legacy.revoke_expiry_time,
+        );
+        registrar.total_sub_created = legacy.total_sub_created;
+        registrar
+    }
 }
 
 impl Registrar {
     pub const SEEDS: &'static [u8; 9] = b"registrar";
+    pub const VAULT_SEEDS: &'static [u8; 5] = b"vault";
 
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -46,12 +170,13 @@ impl Registrar {
         price_schedule: schedule::Schedule,
         nonce: u8,
         nft_gated_collection: Option<Pubkey>,
-        max_nft_mint: u8,
+        max_nft_mint: u32,
         allow_revoke: bool,
         revoke_expiry_time: i64,
     ) -> Self {
         Self {
             tag: super::Tag::Registrar,
+            version: Self::VERSION,
             nonce,
             authority: *authority,
             fee_account: *fee_account,
@@ -63,6 +188,16 @@ impl Registrar {
             max_nft_mint,
             allow_revoke,
             revoke_expiry_time,
+            refund_policy: RefundPolicy::default(),
+            bonding_curve: BondingCurve::default(),
+            soulbound: false,
+            pending_authority: None,
+            timelock_delay: 0,
+            irrevocable_premium_bps: 0,
+            nft_gate_policy: NftGatePolicy::default(),
+            wind_down_deadline: None,
+            created_at: 0,
+            root_domain: RootDomain::default(),
         }
     }
 
@@ -70,17 +205,46 @@ impl Registrar {
         Pubkey::find_program_address(&[Registrar::SEEDS, &domain_account.to_bytes()], program_id)
     }
 
+    // The token account owned by the registrar used to escrow the proceeds
+    pub fn find_vault_key(registrar: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Registrar::VAULT_SEEDS, &registrar.to_bytes()], program_id)
+    }
+
+    // Whether new registrations are stopped, see `WindDown`
+    pub fn is_winding_down(&self) -> bool {
+        self.wind_down_deadline.is_some()
+    }
+
+    // Whether the wind-down deadline has passed
+    pub fn is_wound_down(&self, current_timestamp: i64) -> bool {
+        self.wind_down_deadline
+            .map(|d| current_timestamp >= d)
+            .unwrap_or(false)
+    }
+
     pub fn save(&self, mut dst: &mut [u8]) {
         self.serialize(&mut dst).unwrap()
     }
 
     pub fn from_account_info(a: &AccountInfo, tag: super::Tag) -> Result<Registrar, ProgramError> {
-        let mut data = &a.data.borrow() as &[u8];
-        if data[0] != tag as u8 && data[0] != super::Tag::Uninitialized as u8 {
+        let account_tag = a.data.borrow()[0];
+        if super::Tag::from_u8(account_tag).map(|t| t.current()) != Some(tag)
+            && account_tag != super::Tag::Uninitialized as u8
+        {
             return Err(SubRegisterError::DataTypeMismatch.into());
         }
-        let result = Registrar::deserialize(&mut data)?;
-        Ok(result)
+        Self::deserialize_versioned(a)
+    }
+}
+
+impl Versioned for Registrar {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, data: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            0 => Ok(LegacyRegistrar::deserialize(&mut &data[..])?.into()),
+            _ => Err(unsupported_version(version)),
+        }
     }
 }
 
@@ -95,10 +259,12 @@ mod tests {
     fn test_from_account_info() {
         let registrar: Registrar = Registrar {
             tag: Tag::Registrar,
+            version: Registrar::VERSION,
             ..Registrar::default()
         };
         let closed_registrar: Registrar = Registrar {
             tag: Tag::ClosedRegistrar,
+            version: Registrar::VERSION,
             ..Registrar::default()
         };
         let mut buf: Vec<u8> = vec![0; registrar.borsh_len()];
@@ -169,4 +335,85 @@ mod tests {
         );
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_from_legacy_account_info() {
+        let legacy = LegacyRegistrar {
+            tag: Tag::LegacyRegistrar,
+            nonce: 1,
+            authority: Pubkey::new_unique(),
+            fee_account: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            domain_account: Pubkey::new_unique(),
+            total_sub_created: 3,
+            nft_gated_collection: None,
+            max_nft_mint: u8::MAX,
+            allow_revoke: true,
+            price_schedule: vec![],
+            revoke_expiry_time: 0,
+        };
+        let mut buf = legacy.try_to_vec().unwrap();
+
+        let des = Registrar::from_account_info(
+            &AccountInfo {
+                data: Rc::new(RefCell::new(&mut buf[..])),
+                key: &Pubkey::default(),
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut 0)),
+                owner: &Pubkey::default(),
+                executable: false,
+                rent_epoch: 0,
+            },
+            Tag::Registrar,
+        )
+        .unwrap();
+        assert_eq!(des.tag, Tag::Registrar);
+        assert_eq!(des.version, Registrar::VERSION);
+        assert_eq!(des.max_nft_mint, u8::MAX as u32);
+        assert_eq!(des.total_sub_created, 3);
+
+        // Legacy accounts cannot be written to
+        let res = Registrar::from_account_info(
+            &AccountInfo {
+                data: Rc::new(RefCell::new(&mut buf[..])),
+                key: &Pubkey::default(),
+                is_signer: false,
+                is_writable: true,
+                lamports: Rc::new(RefCell::new(&mut 0)),
+                owner: &Pubkey::default(),
+                executable: false,
+                rent_epoch: 0,
+            },
+            Tag::Registrar,
+        );
+        assert_eq!(res, Err(SubRegisterError::AccountNeedsMigration.into()));
+    }
+
+    #[test]
+    fn test_from_future_account_info() {
+        let registrar: Registrar = Registrar {
+            tag: Tag::Registrar,
+            version: Registrar::VERSION + 1,
+            ..Registrar::default()
+        };
+        let mut buf: Vec<u8> = vec![0; registrar.borsh_len()];
+        registrar.save(&mut buf[..]);
+
+        // Layouts newer than the program cannot be read
+        let res = Registrar::from_account_info(
+            &AccountInfo {
+                data: Rc::new(RefCell::new(&mut buf[..])),
+                key: &Pubkey::default(),
+                is_signer: false,
+                is_writable: false,
+                lamports: Rc::new(RefCell::new(&mut 0)),
+                owner: &Pubkey::default(),
+                executable: false,
+                rent_epoch: 0,
+            },
+            Tag::Registrar,
+        );
+        assert_eq!(res, Err(ProgramError::InvalidAccountData));
+    }
 }
@@ -1,4 +1,4 @@
-use super::Tag;
+use super::{registry::Registrar, unsupported_version, Tag, Versioned};
 use crate::error::SubRegisterError;
 use {
     bonfida_utils::BorshSize,
@@ -13,18 +13,61 @@ pub const REVOKE_EXPIRY_DELAY_SECONDS_MIN: i64 = 604800;
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
 pub struct SubDomainRecord {
     pub tag: Tag,
+    // The layout version of the account
+    pub version: u8,
     // The registrar the record belongs to
     pub registrar: Pubkey,
     // The subdomain key associated to this record
     pub sub_key: Pubkey,
+    // The owner of the subdomain as of `last_owner_sync`, see `SyncOwner`
+    // Kept at a fixed offset so that the subdomains of a wallet can be listed with a memcmp filter
+    pub owner: Pubkey,
     // If the record is associated to a NFT
     pub mint_record: Option<Pubkey>,
     // Expiry timestamp. From this timestamp on the subdomain
     // and subdomain record can be deleted
+    // For active subdomains, the subdomain can be revoked by anyone from this timestamp on
     pub expiry_timestamp: i64,
     // Pubkey of the user who allocated the account
     // Allows for refunds of allocation costs
     pub allocator: Pubkey,
+    // Registration timestamp, used to compute refunds
+    pub registered_at: i64,
+    // The amount received by the registrar vault for this subdomain
+    // i.e the price without the protocol fee, this is the maximum refund
+    // Zero if the proceeds were sent to another fee account
+    pub price_paid: u64,
+    // Restricts the `allow_revoke` setting of the registrar for this subdomain
+    pub allow_revoke: Option<bool>,
+    // For soulbound subdomains, the wallet the subdomain is bound to
+    pub bound_owner: Option<Pubkey>,
+    // The timestamp of the last owner update
+    pub last_owner_sync: i64,
+    // The number of ownership changes recorded since the registration
+    pub transfer_count: u64,
+    // Irrevocable subdomains cannot be revoked by the registrar authority
+    pub irrevocable: bool,
+}
+
+// The layout of the subdomain records created before versioned layouts
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Eq, BorshSize)]
+pub struct LegacySubDomainRecord {
+    pub tag: Tag,
+    pub registrar: Pubkey,
+    pub sub_key: Pubkey,
+    pub mint_record: Option<Pubkey>,
+    pub expiry_timestamp: i64,
+    pub allocator: Pubkey,
+}
+
+impl From<LegacySubDomainRecord> for SubDomainRecord {
+    fn from(legacy: LegacySubDomainRecord) -> Self {
+        let mut record = SubDomainRecord::new(legacy.registrar, legacy.sub_key, legacy.allocator);
+        record.tag = legacy.tag.current();
+        record.mint_record = legacy.mint_record;
+        record.expiry_timestamp = legacy.expiry_timestamp;
+        record
+    }
 }
 
 impl SubDomainRecord {
@@ -33,12 +76,65 @@ impl SubDomainRecord {
     pub fn new(registrar: Pubkey, sub_key: Pubkey, allocator: Pubkey) -> Self {
         Self {
             tag: Tag::SubRecord,
+            version: Self::VERSION,
             registrar,
             sub_key,
+            owner: Pubkey::default(),
             mint_record: None,
             expiry_timestamp: i64::MAX,
             allocator,
+            registered_at: 0,
+            price_paid: 0,
+            allow_revoke: None,
+            bound_owner: None,
+            last_owner_sync: 0,
+            transfer_count: 0,
+            irrevocable: false,
+        }
+    }
+
+    // Whether the registrar authority can revoke the subdomain
+    // The override of the subdomain can only restrict the setting of the registrar
+    pub fn is_revocable(&self, registrar: &Registrar) -> bool {
+        registrar.allow_revoke && self.allow_revoke.unwrap_or(true) && !self.irrevocable
+    }
+
+    // Whether the record was created by a previous registrar at the same address
+    // i.e the registrar was closed after winding down and created again
+    pub fn is_stale(&self, registrar: &Registrar) -> bool {
+        self.registered_at < registrar.created_at
+    }
+
+    // Whether an active subdomain has lapsed
+    pub fn is_expired(&self, current_timestamp: i64) -> bool {
+        self.tag == Tag::SubRecord && current_timestamp >= self.expiry_timestamp
+    }
+
+    // Records the current owner of the subdomain
+    pub fn sync_owner(
+        &mut self,
+        owner: &Pubkey,
+        current_timestamp: i64,
+    ) -> Result<(), ProgramError> {
+        if self.owner == Pubkey::default() {
+            // Legacy records did not track the owner, it is not a transfer
+            self.owner = *owner;
+        } else if self.owner != *owner {
+            self.owner = *owner;
+            self.transfer_count = self
+                .transfer_count
+                .checked_add(1)
+                .ok_or(SubRegisterError::Overflow)?;
         }
+        self.last_owner_sync = current_timestamp;
+        Ok(())
+    }
+
+    // Whether a soulbound subdomain was transferred away from the wallet it is bound to
+    pub fn is_transferred(&self, current_owner: &Pubkey) -> bool {
+        self.bound_owner
+            .map(|o| o != *current_owner)
+            .unwrap_or(false)
     }
 
     pub fn find_key(domain_account: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
@@ -63,13 +159,25 @@ impl SubDomainRecord {
         a: &AccountInfo,
         tag: Option<super::Tag>,
     ) -> Result<SubDomainRecord, ProgramError> {
-        let mut data = &a.data.borrow() as &[u8];
-        if tag.map(|t| data[0] != t as u8).unwrap_or(false)
-            && data[0] != super::Tag::Uninitialized as u8
+        let account_tag = a.data.borrow()[0];
+        if tag
+            .map(|t| super::Tag::from_u8(account_tag).map(|a| a.current()) != Some(t))
+            .unwrap_or(false)
+            && account_tag != super::Tag::Uninitialized as u8
         {
             return Err(SubRegisterError::DataTypeMismatch.into());
         }
-        let result = SubDomainRecord::deserialize(&mut data)?;
-        Ok(result)
+        Self::deserialize_versioned(a)
+    }
+}
+
+impl Versioned for SubDomainRecord {
+    const VERSION: u8 = 1;
+
+    fn upgrade(version: u8, data: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            0 => Ok(LegacySubDomainRecord::deserialize(&mut &data[..])?.into()),
+            _ => Err(unsupported_version(version)),
+        }
     }
 }
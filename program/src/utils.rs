@@ -1,22 +1,36 @@
-use std::convert::TryFrom;
+use std::{cmp::Ordering, convert::TryFrom};
 
 use mpl_token_metadata::accounts::Metadata;
-use solana_program::{program_error::ProgramError, program_pack::Pack};
+use solana_program::{
+    entrypoint::ProgramResult, msg, program::invoke, program_error::ProgramError,
+    program_pack::Pack, rent::Rent, system_instruction, sysvar::Sysvar,
+};
 
 use crate::{
     error::SubRegisterError,
-    state::schedule::{Price, Schedule},
+    state::{
+        bonding_curve::MULTIPLIER_BASE_BPS,
+        delegate::{Delegate, Permission},
+        registry::{RefundKind, RefundPolicy, Registrar},
+        schedule::{Price, Schedule},
+        Tag, MAX_FEE_BPS,
+    },
 };
 
 use {
+    bonfida_utils::checks::{check_account_key, check_account_owner},
     solana_program::{account_info::AccountInfo, hash::hashv, pubkey::Pubkey},
-    spl_name_service::state::{get_seeds_and_key, HASH_PREFIX},
+    spl_name_service::state::{get_seeds_and_key, NameRecordHeader, HASH_PREFIX},
     unicode_segmentation::UnicodeSegmentation,
 };
 
 pub fn get_domain_price(domain: String, schedule: &Schedule) -> u64 {
     let ui_domain = domain.strip_prefix('\0').unwrap();
     let len = ui_domain.graphemes(true).count() as u64;
+    get_price_for_length(len, schedule)
+}
+
+pub fn get_price_for_length(len: u64, schedule: &[Price]) -> u64 {
     let price_index = schedule
         .iter()
         .enumerate()
@@ -26,6 +40,120 @@ pub fn get_domain_price(domain: String, schedule: &Schedule) -> u64 {
     schedule[price_index].price
 }
 
+// Whether no name is more expensive with the `new` schedule than with the `old` one
+// Prices only change at the lengths of the schedules, so checking them is enough
+pub fn is_price_schedule_cheaper(old: &[Price], new: &[Price]) -> bool {
+    if old.is_empty() || new.is_empty() {
+        return false;
+    }
+    old.iter()
+        .chain(new.iter())
+        .map(|p| p.length)
+        .chain(std::iter::once(0))
+        .all(|len| get_price_for_length(len, new) <= get_price_for_length(len, old))
+}
+
+// Computes the current registration price of a subdomain i.e the schedule price
+// scaled by the bonding curve of the registrar. This is the price charged by `register`
+// Irrevocable subdomains are further scaled by the irrevocable premium of the registrar
+pub fn get_registration_price(
+    domain: String,
+    registrar: &Registrar,
+    irrevocable: bool,
+) -> Result<u64, ProgramError> {
+    let price = get_domain_price(domain, &registrar.price_schedule);
+    let price = registrar
+        .bonding_curve
+        .apply(price, registrar.total_sub_created)?;
+    if !irrevocable {
+        return Ok(price);
+    }
+    if registrar.irrevocable_premium_bps == 0 {
+        msg!("The registrar does not offer irrevocable subdomains");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let price =
+        price as u128 * registrar.irrevocable_premium_bps as u128 / MULTIPLIER_BASE_BPS as u128;
+    u64::try_from(price).map_err(|_| SubRegisterError::Overflow.into())
+}
+
+// Computes the protocol fee of a registration in basis points
+// The fee is rounded up so that cheap names cannot round it down to zero, the registrar
+// receives `price - fee`
+pub fn get_protocol_fee(price: u64, fee_bps: u64) -> Result<u64, ProgramError> {
+    if fee_bps > MAX_FEE_BPS {
+        return Err(SubRegisterError::InvalidFee.into());
+    }
+    let fee = (price as u128)
+        .checked_mul(fee_bps as u128)
+        .and_then(|x| x.checked_add(MAX_FEE_BPS as u128 - 1))
+        .ok_or(SubRegisterError::Overflow)?
+        / MAX_FEE_BPS as u128;
+    u64::try_from(fee).map_err(|_| SubRegisterError::Overflow.into())
+}
+
+// Computes the amount refunded when a subdomain is unregistered at `now`
+// Pro rata refunds are rounded down in favor of the registrar
+pub fn get_refund_amount(
+    policy: &RefundPolicy,
+    price_paid: u64,
+    registered_at: i64,
+    now: i64,
+) -> Result<u64, ProgramError> {
+    let elapsed = now.saturating_sub(registered_at).max(0);
+    if policy.window <= 0 || elapsed >= policy.window {
+        return Ok(0);
+    }
+    match policy.kind {
+        RefundKind::None => Ok(0),
+        RefundKind::Full => Ok(price_paid),
+        RefundKind::ProRata => {
+            let remaining = (policy.window - elapsed) as u128;
+            let refund = (price_paid as u128)
+                .checked_mul(remaining)
+                .ok_or(SubRegisterError::Overflow)?
+                / policy.window as u128;
+            u64::try_from(refund).map_err(|_| SubRegisterError::Overflow.into())
+        }
+    }
+}
+
+// Refunds are paid from the registrar vault, a refund policy requires the proceeds to be
+// collected there
+pub fn check_refund_vault(
+    policy: &RefundPolicy,
+    fee_account: &Pubkey,
+    registrar_key: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if policy.kind == RefundKind::None {
+        return Ok(());
+    }
+    let (vault_key, _) = Registrar::find_vault_key(registrar_key, program_id);
+    if fee_account != &vault_key {
+        msg!("The fee account must be the registrar vault {}", vault_key);
+        return Err(SubRegisterError::RefundRequiresVault.into());
+    }
+    Ok(())
+}
+
+// Subdomains are lowercase, prefixed by a null byte and cannot contain a dot
+pub fn check_subdomain_name(domain: &str) -> ProgramResult {
+    if !domain.starts_with('\x00') {
+        return Err(SubRegisterError::InvalidSubdomain.into());
+    }
+
+    if domain.trim().to_lowercase() != domain {
+        return Err(SubRegisterError::InvalidSubdomain.into());
+    }
+
+    if domain.contains('.') {
+        return Err(SubRegisterError::InvalidSubdomain.into());
+    }
+
+    Ok(())
+}
+
 pub fn is_price_schedule_sorted(price_schedule: &[Price]) -> bool {
     price_schedule
         .iter()
@@ -53,7 +181,18 @@ pub fn get_subdomain_key(ui_subdomain: &str, parent: &Pubkey) -> Pubkey {
     name_account_key
 }
 
+// The reverse is derived from the direct parent of the subdomain, which works at any depth
+// e.g for `x.dept.company.sol` the parent is `dept.company.sol`
 pub fn get_subdomain_reverse(ui_subdomain: &str, parent: &Pubkey) -> Pubkey {
+    get_subdomain_reverse_with_class(ui_subdomain, parent, &sns_registrar::central_state::KEY)
+}
+
+// The reverse of a subdomain under a TLD root with another reverse lookup class than .sol
+pub fn get_subdomain_reverse_with_class(
+    ui_subdomain: &str,
+    parent: &Pubkey,
+    reverse_lookup_class: &Pubkey,
+) -> Pubkey {
     let subdomain_key = get_subdomain_key(ui_subdomain, parent);
     let hashed_name = hashv(&[(HASH_PREFIX.to_owned() + &subdomain_key.to_string()).as_bytes()])
         .as_ref()
@@ -62,12 +201,28 @@ pub fn get_subdomain_reverse(ui_subdomain: &str, parent: &Pubkey) -> Pubkey {
     let (name_account_key, _) = get_seeds_and_key(
         &spl_name_service::ID,
         hashed_name,
-        Some(&sns_registrar::central_state::KEY),
+        Some(reverse_lookup_class),
         Some(parent),
     );
     name_account_key
 }
 
+// Returns the TLD root of a domain, whether it is a top level domain or a subdomain at any depth
+// `ancestors` are the name accounts of the parents of the domain, from the direct parent
+// up to the top level domain. It is empty for a top level domain
+pub fn find_domain_root(
+    domain: &NameRecordHeader,
+    ancestors: &[AccountInfo],
+) -> Result<Pubkey, ProgramError> {
+    let mut parent = domain.parent_name;
+    for ancestor in ancestors {
+        check_account_key(ancestor, &parent)?;
+        check_account_owner(ancestor, &spl_name_service::ID)?;
+        parent = NameRecordHeader::unpack_from_slice(&ancestor.data.borrow())?.parent_name;
+    }
+    Ok(parent)
+}
+
 // Assumes the account is owned by SPL Token !!!!
 pub fn check_nft_holding_and_get_mint(
     nft_account: &AccountInfo,
@@ -113,6 +268,113 @@ pub fn check_metadata(
     Err(SubRegisterError::MustHaveCollection.into())
 }
 
+// Checks that `signer` is the registrar authority or a delegate with `permission`
+// The signer must be checked by the caller
+pub fn check_authority_or_delegate(
+    program_id: &Pubkey,
+    registrar_key: &Pubkey,
+    registrar: &Registrar,
+    signer: &AccountInfo,
+    delegate_account: Option<&AccountInfo>,
+    permission: Permission,
+) -> ProgramResult {
+    if signer.key == &registrar.authority {
+        return Ok(());
+    }
+    let delegate_account = delegate_account.ok_or(SubRegisterError::MissingPermission)?;
+    let (delegate_key, _) = Delegate::find_key(registrar_key, signer.key, program_id);
+    if delegate_account.key != &delegate_key || delegate_account.owner != program_id {
+        return Err(SubRegisterError::MissingPermission.into());
+    }
+    let delegate = Delegate::from_account_info(delegate_account, Tag::Delegate)?;
+    if delegate.authority != registrar.authority {
+        msg!("The delegate was appointed by a previous authority");
+        return Err(SubRegisterError::MissingPermission.into());
+    }
+    if !delegate.has_permission(permission) {
+        msg!("The delegate is missing the {:?} permission", permission);
+        return Err(SubRegisterError::MissingPermission.into());
+    }
+    Ok(())
+}
+
+// Returns the next account if it is owned by `owner` and holds a `tag` account, legacy included
+// Used to tell optional accounts apart when some of them are omitted
+pub fn next_account_with_tag<'a, 'b>(
+    iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    owner: &Pubkey,
+    tag: Tag,
+) -> Option<&'a AccountInfo<'b>> {
+    let next = iter.as_slice().first()?;
+    let is_tagged = next.owner == owner
+        && next
+            .data
+            .borrow()
+            .first()
+            .and_then(|t| Tag::from_u8(*t))
+            .map_or(false, |t| t.current() == tag);
+    if is_tagged {
+        iter.next()
+    } else {
+        None
+    }
+}
+
+// Returns the account of `key` among the accounts of the instruction
+// CPIs can only reference accounts passed to the instruction, e.g the owner of a new subdomain
+pub fn find_account<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    key: &Pubkey,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    accounts.iter().find(|a| a.key == key).ok_or_else(|| {
+        msg!("Missing account {}", key);
+        SubRegisterError::MissingAccount.into()
+    })
+}
+
+// Resizes a program account, `payer` funds or receives the rent difference
+pub fn realloc_account<'a>(
+    account: &AccountInfo<'a>,
+    new_len: usize,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    match new_len.cmp(&account.data_len()) {
+        Ordering::Greater => {
+            msg!("[+] Realloc account (increasing size)");
+            let new_lamports = Rent::get()?.minimum_balance(new_len);
+            let diff_lamports = new_lamports.checked_sub(account.lamports());
+
+            account.realloc(new_len, false)?;
+
+            if let Some(diff_lamports) = diff_lamports {
+                let ix = system_instruction::transfer(payer.key, account.key, diff_lamports);
+                invoke(
+                    &ix,
+                    &[system_program.clone(), payer.clone(), account.clone()],
+                )?;
+            }
+        }
+        Ordering::Less => {
+            msg!("[+] Realloc account (decreasing size)");
+            let new_lamports = Rent::get()?.minimum_balance(new_len);
+            let diff_lamports = account.lamports().checked_sub(new_lamports);
+
+            account.realloc(new_len, true)?;
+
+            if let Some(diff_lamports) = diff_lamports {
+                let mut account_lamports = account.lamports.borrow_mut();
+                let mut payer_lamports = payer.lamports.borrow_mut();
+
+                **payer_lamports += diff_lamports;
+                **account_lamports -= diff_lamports;
+            }
+        }
+        Ordering::Equal => (),
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use borsh::BorshSerialize;
@@ -223,6 +485,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_price_schedule_cheaper() {
+        let schedule = vec![
+            Price {
+                length: 1,
+                price: 100,
+            },
+            Price {
+                length: 3,
+                price: 50,
+            },
+        ];
+        assert!(is_price_schedule_cheaper(&schedule, &schedule));
+
+        // Lower prices
+        let cheaper = vec![
+            Price {
+                length: 1,
+                price: 80,
+            },
+            Price {
+                length: 2,
+                price: 50,
+            },
+        ];
+        assert!(is_price_schedule_cheaper(&schedule, &cheaper));
+        assert!(!is_price_schedule_cheaper(&cheaper, &schedule));
+
+        // Moving a threshold up increases the price of length 3 names
+        let shifted = vec![
+            Price {
+                length: 1,
+                price: 100,
+            },
+            Price {
+                length: 4,
+                price: 10,
+            },
+        ];
+        assert!(!is_price_schedule_cheaper(&schedule, &shifted));
+
+        assert!(!is_price_schedule_cheaper(&schedule, &[]));
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        // 5%
+        assert_eq!(get_protocol_fee(0, 500).unwrap(), 0);
+        assert_eq!(get_protocol_fee(1, 500).unwrap(), 1);
+        assert_eq!(get_protocol_fee(19, 500).unwrap(), 1);
+        assert_eq!(get_protocol_fee(20, 500).unwrap(), 1);
+        assert_eq!(get_protocol_fee(21, 500).unwrap(), 2);
+        assert_eq!(get_protocol_fee(8_000_000, 500).unwrap(), 400_000);
+        assert_eq!(
+            get_protocol_fee(u64::MAX, 500).unwrap(),
+            922_337_203_685_477_581
+        );
+
+        // Fractional percentages
+        assert_eq!(get_protocol_fee(10_000, 1).unwrap(), 1);
+        assert_eq!(get_protocol_fee(10_001, 1).unwrap(), 2);
+        assert_eq!(get_protocol_fee(1_000_000, 125).unwrap(), 12_500);
+
+        // Bounds
+        assert_eq!(get_protocol_fee(u64::MAX, 0).unwrap(), 0);
+        assert_eq!(get_protocol_fee(u64::MAX, MAX_FEE_BPS).unwrap(), u64::MAX);
+        assert_eq!(get_protocol_fee(19, MAX_FEE_BPS).unwrap(), 19);
+        assert!(get_protocol_fee(1, MAX_FEE_BPS + 1).is_err());
+    }
+
+    #[test]
+    fn test_refund_amount() {
+        let full = RefundPolicy {
+            kind: RefundKind::Full,
+            window: 3_600,
+        };
+        assert_eq!(get_refund_amount(&full, 1_000, 100, 100).unwrap(), 1_000);
+        assert_eq!(get_refund_amount(&full, 1_000, 100, 3_699).unwrap(), 1_000);
+        assert_eq!(get_refund_amount(&full, 1_000, 100, 3_700).unwrap(), 0);
+
+        let pro_rata = RefundPolicy {
+            kind: RefundKind::ProRata,
+            window: 1_000,
+        };
+        assert_eq!(get_refund_amount(&pro_rata, 1_000, 0, 0).unwrap(), 1_000);
+        assert_eq!(get_refund_amount(&pro_rata, 1_000, 0, 250).unwrap(), 750);
+        assert_eq!(get_refund_amount(&pro_rata, 999, 0, 1).unwrap(), 998);
+        assert_eq!(get_refund_amount(&pro_rata, 1_000, 0, 1_000).unwrap(), 0);
+        assert_eq!(
+            get_refund_amount(&pro_rata, u64::MAX, 0, 500).unwrap(),
+            u64::MAX / 2
+        );
+
+        // Clock drift before the registration
+        assert_eq!(get_refund_amount(&pro_rata, 1_000, 10, 0).unwrap(), 1_000);
+
+        let none = RefundPolicy {
+            kind: RefundKind::None,
+            window: 1_000,
+        };
+        assert_eq!(get_refund_amount(&none, 1_000, 0, 0).unwrap(), 0);
+        assert_eq!(
+            get_refund_amount(&RefundPolicy::default(), 1_000, 0, 0).unwrap(),
+            0
+        );
+    }
+
     #[test]
     fn test_check_nft_holding_and_get_mint() {
         let owner = Pubkey::new_unique();
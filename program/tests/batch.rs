@@ -0,0 +1,349 @@
+use spl_name_service::state::NameRecordHeader;
+use sub_register::{
+    entrypoint::process_instruction,
+    error::SubRegisterError,
+    instruction::{admin_register_batch, admin_revoke_batch, create_registrar},
+    processor::{admin_register_batch::MAX_BATCH_SIZE, admin_revoke_batch::REVOKED},
+    state::{
+        config::Config,
+        registry::Registrar,
+        schedule::Price,
+        subdomain_record::{SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN},
+        Tag, ROOT_DOMAIN_ACCOUNT,
+    },
+};
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{instruction::Instruction, program_pack::Pack, system_program, sysvar},
+    solana_program_test::{processor, ProgramTest},
+    solana_sdk::{
+        account::Account,
+        compute_budget::ComputeBudgetInstruction,
+        message::Message,
+        packet::PACKET_DATA_SIZE,
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    },
+};
+
+pub mod common;
+
+fn get_batch_instruction(
+    registry_key: &Pubkey,
+    name_key: &Pubkey,
+    authority: &Pubkey,
+    items: Vec<admin_register_batch::Item>,
+) -> Instruction {
+    let mut sub_accounts = vec![];
+    for item in items.iter() {
+        let ui_domain = item.domain.strip_prefix('\0').unwrap();
+        let sub_domain_key = sub_register::utils::get_subdomain_key(ui_domain, name_key);
+        let sub_reverse_key = sub_register::utils::get_subdomain_reverse(ui_domain, name_key);
+        let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+        sub_accounts.extend([sub_domain_key, sub_reverse_key, subrecord_key]);
+    }
+    // The recipients are appended after the subdomain accounts
+    let mut recipients = items.iter().filter_map(|i| i.recipient).collect::<Vec<_>>();
+    recipients.sort();
+    recipients.dedup();
+    sub_accounts.extend(recipients);
+    admin_register_batch(
+        admin_register_batch::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: registry_key,
+            parent_domain_account: name_key,
+            authority,
+            sub_accounts: &sub_accounts,
+            delegate: None,
+        },
+        admin_register_batch::Params { items },
+    )
+}
+
+fn get_revoke_batch_instruction(
+    registry_key: &Pubkey,
+    name_key: &Pubkey,
+    authority: &Pubkey,
+    subs: &[(&str, Pubkey)],
+) -> Instruction {
+    let mut sub_accounts = vec![];
+    for (domain, owner) in subs {
+        let ui_domain = domain.strip_prefix('\0').unwrap();
+        let sub_domain_key = sub_register::utils::get_subdomain_key(ui_domain, name_key);
+        let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+        sub_accounts.extend([sub_domain_key, subrecord_key, *owner]);
+    }
+    admin_revoke_batch(
+        admin_revoke_batch::Accounts {
+            registrar: registry_key,
+            parent_domain: name_key,
+            authority,
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            sub_accounts: &sub_accounts,
+            delegate: None,
+        },
+        admin_revoke_batch::Params {},
+    )
+}
+
+fn get_items(n: usize) -> Vec<admin_register_batch::Item> {
+    (0..n)
+        .map(|_| admin_register_batch::Item {
+            domain: format!("\0{}", common::utils::random_string()),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_admin_register_batch() {
+    // Create program and test environment
+    use common::utils::{sign_send_instructions, simulate_instructions};
+
+    // Alice owns a .sol and creates the registry
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let mint = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+
+    program_test.add_program("spl_name_service", spl_name_service::ID, None);
+    program_test.add_program("sns_registrar", sns_registrar::ID, None);
+
+    program_test.add_account(
+        alice.pubkey(),
+        Account {
+            lamports: 100_000_000_000,
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        sns_registrar::central_state::KEY,
+        Account {
+            lamports: 1_000_000,
+            owner: sns_registrar::ID,
+            data: vec![sns_registrar::central_state::NONCE],
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        ROOT_DOMAIN_ACCOUNT,
+        Account {
+            lamports: 1_000_000,
+            owner: spl_name_service::ID,
+            ..Account::default()
+        },
+    );
+
+    // Create mock .sol domain
+    let name_key = Keypair::new().pubkey();
+    let root_domain_data = NameRecordHeader {
+        parent_name: ROOT_DOMAIN_ACCOUNT,
+        owner: alice.pubkey(),
+        class: Pubkey::default(),
+    }
+    .try_to_vec()
+    .unwrap();
+    program_test.add_account(
+        name_key,
+        Account {
+            lamports: 1_000_000,
+            data: root_domain_data,
+            owner: spl_name_service::id(),
+            ..Account::default()
+        },
+    );
+
+    ////
+    // Create test context
+    ////
+    let mut prg_test_ctx = program_test.start_with_context().await;
+
+    // Alice creates registry
+    let (registry_key, _) = Registrar::find_key(&name_key, &sub_register::ID);
+    let ix = create_registrar(
+        create_registrar::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            domain_name_account: &name_key,
+            domain_owner: &alice.pubkey(),
+            fee_payer: &prg_test_ctx.payer.pubkey(),
+            spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
+        },
+        create_registrar::Params {
+            mint,
+            fee_account: Pubkey::new_unique(),
+            nft_gated_collection: None,
+            max_nft_mint: 0,
+            allow_revoke: true,
+            authority: alice.pubkey(),
+            price_schedule: common::utils::serialize_price_schedule(&[Price {
+                length: 1,
+                price: 10_000_000,
+            }]),
+            revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    // A full batch fits in a legacy transaction, one more subdomain does not
+    let compute_ix = ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
+    let payer = prg_test_ctx.payer.pubkey();
+    let get_transaction_size = |n: usize| {
+        let ix = get_batch_instruction(&registry_key, &name_key, &alice.pubkey(), get_items(n));
+        let message = Message::new(&[compute_ix.clone(), ix], Some(&payer));
+        // Compact length and signatures followed by the message
+        1 + 64 * message.header.num_required_signatures as usize + message.serialize().len()
+    };
+    assert!(get_transaction_size(MAX_BATCH_SIZE) <= PACKET_DATA_SIZE);
+    assert!(get_transaction_size(MAX_BATCH_SIZE + 1) > PACKET_DATA_SIZE);
+
+    // A full batch stays within the maximum compute budget
+    let items = get_items(MAX_BATCH_SIZE);
+    let ix = get_batch_instruction(&registry_key, &name_key, &alice.pubkey(), items);
+    let (batch_units, _) = simulate_instructions(
+        &mut prg_test_ctx,
+        vec![compute_ix.clone(), ix],
+        vec![&alice],
+    )
+    .await
+    .unwrap();
+    assert!(batch_units < 1_400_000);
+
+    // Register a full batch, the first sub cannot be revoked and the last sub is owned by Bob
+    let mut items = get_items(MAX_BATCH_SIZE);
+    items[0].allow_revoke = Some(false);
+    items[MAX_BATCH_SIZE - 1].recipient = Some(bob.pubkey());
+    let domains = items.iter().map(|i| i.domain.clone()).collect::<Vec<_>>();
+    let bob_domain = items[MAX_BATCH_SIZE - 1].domain.clone();
+    let ix = get_batch_instruction(&registry_key, &name_key, &alice.pubkey(), items);
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![compute_ix.clone(), ix],
+        vec![&alice],
+    )
+    .await
+    .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(registrar.total_sub_created, MAX_BATCH_SIZE as u64);
+
+    let bob_sub_key =
+        sub_register::utils::get_subdomain_key(bob_domain.strip_prefix('\0').unwrap(), &name_key);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_sub_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.owner, bob.pubkey());
+
+    // Batches are limited in size
+    let ix = get_batch_instruction(
+        &registry_key,
+        &name_key,
+        &alice.pubkey(),
+        get_items(MAX_BATCH_SIZE + 1),
+    );
+    let res = sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![compute_ix.clone(), ix],
+        vec![&alice],
+    )
+    .await;
+    assert!(res.is_err());
+
+    // Missing accounts
+    let mut ix = get_batch_instruction(&registry_key, &name_key, &alice.pubkey(), get_items(2));
+    ix.accounts.pop();
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    let mut items = get_items(1);
+    items[0].recipient = Some(bob.pubkey());
+    let mut ix = get_batch_instruction(&registry_key, &name_key, &alice.pubkey(), items);
+    ix.accounts.pop();
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Only the authority can register
+    let ix = get_batch_instruction(&registry_key, &name_key, &bob.pubkey(), get_items(1));
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+
+    // Alice revokes several subs, invalid items are skipped
+    let subs = [
+        (domains[0].as_str(), alice.pubkey()),
+        (domains[1].as_str(), alice.pubkey()),
+        (domains[2].as_str(), alice.pubkey()),
+        (domains[2].as_str(), alice.pubkey()),
+        (bob_domain.as_str(), alice.pubkey()),
+        (bob_domain.as_str(), bob.pubkey()),
+    ];
+    let ix = get_revoke_batch_instruction(&registry_key, &name_key, &alice.pubkey(), &subs);
+    let (_, return_data) = simulate_instructions(&mut prg_test_ctx, vec![ix.clone()], vec![&alice])
+        .await
+        .unwrap();
+    let results = Vec::<u64>::try_from_slice(&return_data).unwrap();
+    assert_eq!(results.len(), subs.len());
+    assert_eq!(results[0], SubRegisterError::CannotRevoke as u64);
+    assert_eq!(results[1], REVOKED);
+    assert_eq!(results[2], REVOKED);
+    assert_ne!(results[3], REVOKED);
+    assert_ne!(results[4], REVOKED);
+    assert_eq!(results[5], REVOKED);
+
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    // Revoked subrecords are accounted for until they are deleted
+    assert_eq!(registrar.total_sub_created, MAX_BATCH_SIZE as u64);
+
+    let bob_subrecord_key = SubDomainRecord::find_key(&bob_sub_key, &sub_register::ID).0;
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(sub_record.tag, Tag::RevokedSubRecord);
+
+    // Only the authority can revoke
+    let subs = [(domains[3].as_str(), alice.pubkey())];
+    let ix = get_revoke_batch_instruction(&registry_key, &name_key, &bob.pubkey(), &subs);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+}
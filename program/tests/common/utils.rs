@@ -30,6 +30,27 @@ pub async fn sign_send_instructions(
     ctx.banks_client.process_transaction(transaction).await
 }
 
+// Simulates the instructions, returns the compute units consumed and the return data
+pub async fn simulate_instructions(
+    ctx: &mut ProgramTestContext,
+    instructions: Vec<Instruction>,
+    signers: Vec<&Keypair>,
+) -> Result<(u64, Vec<u8>), BanksClientError> {
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&ctx.payer.pubkey()));
+    let mut payer_signers = vec![&ctx.payer];
+    for s in signers {
+        payer_signers.push(s);
+    }
+    transaction.partial_sign(&payer_signers, ctx.last_blockhash);
+    let res = ctx.banks_client.simulate_transaction(transaction).await?;
+    if let Some(Err(e)) = res.result {
+        return Err(BanksClientError::TransactionError(e));
+    }
+    let details = res.simulation_details.unwrap();
+    let return_data = details.return_data.map(|r| r.data).unwrap_or_default();
+    Ok((details.units_consumed, return_data))
+}
+
 pub fn mint_bootstrap(
     address: Option<&str>,
     decimals: u8,
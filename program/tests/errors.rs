@@ -7,6 +7,7 @@ use sub_register::{
         edit_registrar, nft_owner_revoke, register, unregister,
     },
     state::{
+        config::Config,
         mint_record::MintRecord,
         registry::Registrar,
         schedule::Price,
@@ -269,6 +270,8 @@ async fn test_errors() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             mint,
@@ -334,6 +337,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -341,6 +345,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -381,6 +387,8 @@ async fn test_errors() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             mint,
@@ -412,9 +420,10 @@ async fn test_errors() {
             system_program: &system_program::ID,
             authority: &fake_authority.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
-            new_authority: None,
             new_mint: None,
             new_fee_account: None,
             new_max_nft_mint: None,
@@ -432,6 +441,15 @@ async fn test_errors() {
                     price: 5_000_000,
                 },
             ])),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     let result = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&fake_authority]).await;
@@ -447,6 +465,8 @@ async fn test_errors() {
             lamports_target: &mint_authority.pubkey(),
             registry_authority: &alice.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            spl_token_program: &spl_token::ID,
+            vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
         },
         close_registrar::Params {},
     );
@@ -464,6 +484,8 @@ async fn test_errors() {
             lamports_target: &mint_authority.pubkey(),
             registry_authority: &fake_authority.pubkey(), // <- Fake authority and same signer
             spl_name_program_id: &spl_name_service::ID,
+            spl_token_program: &spl_token::ID,
+            vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
         },
         close_registrar::Params {},
     );
@@ -503,6 +525,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -510,6 +533,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     let result = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
@@ -533,6 +558,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -540,6 +566,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     let result = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
@@ -568,6 +596,9 @@ async fn test_errors() {
             domain_owner: &bob.pubkey(),
             sub_record: &subrecord_key,
             mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
         },
         unregister::Params {},
     );
@@ -591,6 +622,8 @@ async fn test_errors() {
                     lamports_target: &mint_authority.pubkey(),
                     registry_authority: &alice.pubkey(),
                     spl_name_program_id: &spl_name_service::ID,
+                    spl_token_program: &spl_token::ID,
+                    vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
                 },
                 close_registrar::Params {},
             ),
@@ -611,6 +644,7 @@ async fn test_errors() {
                     sub_reverse_account: &sub_reverse_key,
                     fee_payer: &bob.pubkey(),
                     bonfida_fee_account,
+                    config: &Config::find_key(&sub_register::ID).0,
                     nft_account: None,
                     nft_metadata_account: None,
                     sub_record: &subrecord_key,
@@ -618,6 +652,8 @@ async fn test_errors() {
                 },
                 register::Params {
                     domain: format!("\0{}", sub_domain),
+                    recipient: None,
+                    irrevocable: false,
                 },
             ),
         ],
@@ -650,12 +686,17 @@ async fn test_errors() {
                 sub_reverse_account: &sub_reverse_key,
                 fee_payer: &bob.pubkey(),
                 bonfida_fee_account,
+                config: &Config::find_key(&sub_register::ID).0,
                 nft_account: None,
                 nft_metadata_account: None,
                 sub_record: &subrecord_key,
                 nft_mint_record: None,
             },
-            register::Params { domain: sub_domain },
+            register::Params {
+                domain: sub_domain,
+                recipient: None,
+                irrevocable: false,
+            },
         )],
         vec![&bob],
     )
@@ -674,6 +715,9 @@ async fn test_errors() {
                 domain_owner: &bob.pubkey(),
                 sub_record: &subrecord_key,
                 mint_record: None,
+                spl_token_program: None,
+                vault: None,
+                refund_destination: None,
             },
             unregister::Params {},
         )],
@@ -704,9 +748,13 @@ async fn test_errors() {
                 sub_reverse_account: &sub_reverse_key,
                 authority: &bob.pubkey(),
                 sub_record: &subrecord_key,
+                delegate: None,
             },
             admin_register::Params {
                 domain: format!("\0{}", sub_domain),
+                recipient: None,
+                expiry_timestamp: None,
+                allow_revoke: None,
             },
         )],
         vec![&bob],
@@ -738,6 +786,7 @@ async fn test_errors() {
                 sub_reverse_account: &sub_reverse_key,
                 fee_payer: &bob.pubkey(),
                 bonfida_fee_account: alice_fee_account,
+                config: &Config::find_key(&sub_register::ID).0,
                 nft_account: None,
                 nft_metadata_account: None,
                 sub_record: &subrecord_key,
@@ -745,6 +794,8 @@ async fn test_errors() {
             },
             register::Params {
                 domain: format!("\0{}", sub_domain),
+                recipient: None,
+                irrevocable: false,
             },
         )],
         vec![&bob],
@@ -767,6 +818,8 @@ async fn test_errors() {
             lamports_target: &mint_authority.pubkey(),
             registry_authority: &alice.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            spl_token_program: &spl_token::ID,
+            vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
         },
         close_registrar::Params {},
     );
@@ -781,6 +834,8 @@ async fn test_errors() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             max_nft_mint: 4,
@@ -835,6 +890,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account_zero_amount),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -842,6 +898,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
@@ -875,6 +933,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -882,6 +941,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
@@ -910,6 +971,8 @@ async fn test_errors() {
                 lamports_target: &mint_authority.pubkey(),
                 registry_authority: &alice.pubkey(),
                 spl_name_program_id: &spl_name_service::ID,
+                spl_token_program: &spl_token::ID,
+                vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
             },
             close_registrar::Params {},
         )],
@@ -928,6 +991,8 @@ async fn test_errors() {
                     domain_owner: &alice.pubkey(),
                     fee_payer: &fee_payer,
                     spl_name_program_id: &spl_name_service::ID,
+                    config: &Config::find_key(&sub_register::ID).0,
+                    ancestor_domains: &[],
                 },
                 create_registrar::Params {
                     max_nft_mint: 4,
@@ -966,6 +1031,7 @@ async fn test_errors() {
                     sub_reverse_account: &sub_reverse_key_1,
                     fee_payer: &bob.pubkey(),
                     bonfida_fee_account,
+                    config: &Config::find_key(&sub_register::ID).0,
                     nft_account: None,
                     nft_metadata_account: None,
                     sub_record: &subrecord_key_1,
@@ -973,6 +1039,8 @@ async fn test_errors() {
                 },
                 register::Params {
                     domain: format!("\0{}", sub_domain_1),
+                    recipient: None,
+                    irrevocable: false,
                 },
             ),
             register(
@@ -992,6 +1060,7 @@ async fn test_errors() {
                     sub_reverse_account: &sub_reverse_key_2,
                     fee_payer: &bob.pubkey(),
                     bonfida_fee_account,
+                    config: &Config::find_key(&sub_register::ID).0,
                     nft_account: None,
                     nft_metadata_account: None,
                     sub_record: &subrecord_key_2,
@@ -999,6 +1068,8 @@ async fn test_errors() {
                 },
                 register::Params {
                     domain: format!("\0{}", sub_domain_2),
+                    recipient: None,
+                    irrevocable: false,
                 },
             ),
         ],
@@ -1018,6 +1089,9 @@ async fn test_errors() {
                 domain_owner: &bob.pubkey(),
                 sub_record: &subrecord_key_2,
                 mint_record: Some(&mint_record),
+                spl_token_program: None,
+                vault: None,
+                refund_destination: None,
             },
             unregister::Params {},
         )],
@@ -1074,6 +1148,9 @@ async fn test_errors() {
                     domain_owner: &bob.pubkey(),
                     sub_record: &subrecord_key_2,
                     mint_record: None,
+                    spl_token_program: None,
+                    vault: None,
+                    refund_destination: None,
                 },
                 unregister::Params {},
             ),
@@ -1086,6 +1163,8 @@ async fn test_errors() {
                     lamports_target: &mint_authority.pubkey(),
                     registry_authority: &alice.pubkey(),
                     spl_name_program_id: &spl_name_service::ID,
+                    spl_token_program: &spl_token::ID,
+                    vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
                 },
                 close_registrar::Params {},
             ),
@@ -1104,6 +1183,8 @@ async fn test_errors() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             max_nft_mint: 4,
@@ -1152,6 +1233,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -1159,6 +1241,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1175,6 +1259,7 @@ async fn test_errors() {
             name_class: &Pubkey::default(),
             spl_name_service: &spl_name_service::ID,
             mint_record: None,
+            delegate: None,
         },
         admin_revoke::Params {},
     );
@@ -1194,6 +1279,9 @@ async fn test_errors() {
                     domain_owner: &bob.pubkey(),
                     sub_record: &subrecord_key,
                     mint_record: None,
+                    spl_token_program: None,
+                    vault: None,
+                    refund_destination: None,
                 },
                 unregister::Params {},
             ),
@@ -1206,6 +1294,8 @@ async fn test_errors() {
                     lamports_target: &mint_authority.pubkey(),
                     registry_authority: &alice.pubkey(),
                     spl_name_program_id: &spl_name_service::ID,
+                    spl_token_program: &spl_token::ID,
+                    vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
                 },
                 close_registrar::Params {},
             ),
@@ -1224,6 +1314,8 @@ async fn test_errors() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             max_nft_mint: 4,
@@ -1271,6 +1363,7 @@ async fn test_errors() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -1278,6 +1371,8 @@ async fn test_errors() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1294,6 +1389,7 @@ async fn test_errors() {
             name_class: &Pubkey::default(),
             spl_name_service: &spl_name_service::ID,
             mint_record: None,
+            delegate: None,
         },
         admin_revoke::Params {},
     );
@@ -1481,6 +1577,8 @@ async fn test_errors_nft() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             mint,
@@ -1549,6 +1647,7 @@ async fn test_errors_nft() {
                 sub_reverse_account: &sub_reverse_key,
                 fee_payer: &bob.pubkey(),
                 bonfida_fee_account,
+                config: &Config::find_key(&sub_register::ID).0,
                 nft_account: Some(&bob_nft_account),
                 nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
                 sub_record: &subrecord_key,
@@ -1556,6 +1655,8 @@ async fn test_errors_nft() {
             },
             register::Params {
                 domain: format!("\0{}", sub_domain),
+                recipient: None,
+                irrevocable: false,
             },
         );
         sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1591,6 +1692,7 @@ async fn test_errors_nft() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1598,6 +1700,8 @@ async fn test_errors_nft() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
@@ -1686,6 +1790,7 @@ async fn test_errors_nft() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1693,6 +1798,8 @@ async fn test_errors_nft() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1711,6 +1818,7 @@ async fn test_errors_nft() {
             spl_name_service: &spl_name_service::ID,
             name_class: &Pubkey::default(),
             mint_record: Some(&mint_record),
+            delegate: None,
         },
         admin_revoke::Params {},
     );
@@ -1747,6 +1855,7 @@ async fn test_errors_nft() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1754,6 +1863,8 @@ async fn test_errors_nft() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
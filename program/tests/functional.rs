@@ -1,20 +1,25 @@
-use solana_program::program_pack::Pack;
+use solana_program::{instruction::AccountMeta, program_pack::Pack};
+use spl_name_service::state::NameRecordHeader;
 use sub_register::{
     entrypoint::process_instruction,
     instruction::{
-        admin_register, admin_revoke, close_registrar, create_registrar, delete_subdomain_record,
-        edit_registrar, nft_owner_revoke, register, unregister,
+        admin_register, admin_revoke, close_registrar, create_registrar, create_vault,
+        delete_subdomain_record, edit_registrar, execute_change, nft_owner_revoke, quote_price,
+        register, sync_owner, unregister, withdraw,
     },
     state::{
+        bonding_curve::{BondingCurve, CurveKind},
+        config::Config,
         mint_record::MintRecord,
-        registry::Registrar,
+        pending_change::{PendingChange, RegistrarChange},
+        registry::{RefundKind, RefundPolicy, Registrar},
         schedule::Price,
         subdomain_record::{SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN},
         FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
     },
 };
 use {
-    borsh::BorshSerialize,
+    borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{system_program, sysvar},
     solana_program_test::{processor, ProgramTest},
     solana_sdk::{
@@ -31,7 +36,9 @@ pub mod common;
 #[tokio::test]
 async fn test_functional() {
     // Create program and test environment
-    use common::utils::{random_string, sign_send_instructions};
+    use common::utils::{
+        random_string, sign_send_instructions, simulate_instructions, ProgramTestContextExtended,
+    };
 
     // Alice owns a .sol and creates the registry
     let alice = Keypair::new();
@@ -208,6 +215,8 @@ async fn test_functional() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             mint,
@@ -239,10 +248,11 @@ async fn test_functional() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: Some(1),
-            new_authority: None,
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: Some(common::utils::serialize_price_schedule(&vec![
@@ -259,6 +269,15 @@ async fn test_functional() {
                     price: 5_000_000,
                 },
             ])),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -281,13 +300,23 @@ async fn test_functional() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
-            new_authority: None,
             new_max_nft_mint: None,
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: Some(common::utils::serialize_price_schedule(&price_schedule)),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -317,6 +346,7 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -324,6 +354,8 @@ async fn test_functional() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -338,6 +370,9 @@ async fn test_functional() {
             domain_owner: &bob.pubkey(),
             sub_record: &subrecord_key,
             mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
         },
         unregister::Params {},
     );
@@ -369,6 +404,7 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             nft_mint_record: None,
@@ -376,6 +412,8 @@ async fn test_functional() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -404,15 +442,649 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             authority: &alice.pubkey(),
             sub_record: &subrecord_key_to_unreg_2.clone(),
+            delegate: None,
         },
         admin_register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
         .await
         .unwrap();
 
+    // Bob registers a subdomain for Carol
+    let carol = Keypair::new();
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let mut ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: alice_fee_account,
+            fee_source: &bob_ata,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: None,
+            nft_metadata_account: None,
+            nft_mint_record: None,
+            sub_record: &subrecord_key,
+        },
+        register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: Some(carol.pubkey()),
+            irrevocable: false,
+        },
+    );
+    ix.accounts
+        .push(AccountMeta::new_readonly(carol.pubkey(), false));
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+
+    // Carol owns the subdomain, Bob allocated the subrecord
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(sub_domain_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.owner, carol.pubkey());
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let subrecord = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(subrecord.allocator, bob.pubkey());
+    assert_eq!(subrecord.owner, carol.pubkey());
+
+    // Carol transfers the subdomain to Bob, anyone can record the new owner
+    let ix = spl_name_service::instruction::transfer(
+        spl_name_service::ID,
+        bob.pubkey(),
+        sub_domain_key,
+        carol.pubkey(),
+        None,
+    )
+    .unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&carol])
+        .await
+        .unwrap();
+    let ix = sync_owner(
+        sync_owner::Accounts {
+            sub_domain_account: &sub_domain_key,
+            sub_record: &subrecord_key,
+        },
+        sync_owner::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let subrecord = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(subrecord.owner, bob.pubkey());
+    assert_eq!(subrecord.transfer_count, 1);
+
+    // Bob gives it back
+    let ix = spl_name_service::instruction::transfer(
+        spl_name_service::ID,
+        carol.pubkey(),
+        sub_domain_key,
+        bob.pubkey(),
+        None,
+    )
+    .unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+
+    // Bob cannot unregister it
+    let ix = unregister(
+        unregister::Accounts {
+            system_program: &system_program::ID,
+            spl_name_service: &spl_name_service::ID,
+            registrar: &registry_key,
+            sub_domain_account: &sub_domain_key,
+            domain_owner: &bob.pubkey(),
+            sub_record: &subrecord_key,
+            mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
+        },
+        unregister::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+
+    let ix = unregister(
+        unregister::Accounts {
+            system_program: &system_program::ID,
+            spl_name_service: &spl_name_service::ID,
+            registrar: &registry_key,
+            sub_domain_account: &sub_domain_key,
+            domain_owner: &carol.pubkey(),
+            sub_record: &subrecord_key,
+            mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
+        },
+        unregister::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&carol])
+        .await
+        .unwrap();
+
+    // Alice creates a vault to escrow the proceeds
+    let (vault, _) = Registrar::find_vault_key(&registry_key, &sub_register::ID);
+    let ix = create_vault(
+        create_vault::Accounts {
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            registrar: &registry_key,
+            vault: &vault,
+            mint: &mint,
+            authority: &alice.pubkey(),
+        },
+        create_vault::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_ne!(registrar.fee_account, vault);
+
+    // Refunds cannot be offered while the proceeds bypass the vault
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: None,
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: Some(RefundPolicy {
+                kind: RefundKind::Full,
+                window: 3_600,
+            }),
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Alice redirects the proceeds to the vault and allows full refunds within an hour
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: Some(vault),
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: Some(RefundPolicy {
+                kind: RefundKind::Full,
+                window: 3_600,
+            }),
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    // Bob registers a subdomain and unregisters it right away
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_ata)
+        .await
+        .unwrap()
+        .unwrap();
+    let bob_balance = spl_token::state::Account::unpack(&acc.data[..])
+        .unwrap()
+        .amount;
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: &vault,
+            fee_source: &bob_ata,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: None,
+            nft_metadata_account: None,
+            nft_mint_record: None,
+            sub_record: &subrecord_key,
+        },
+        register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let price_paid = SubDomainRecord::deserialize(&mut &acc.data[..])
+        .unwrap()
+        .price_paid;
+    assert!(price_paid > 0);
+
+    // The refund accounts are required
+    let ix = unregister(
+        unregister::Accounts {
+            system_program: &system_program::ID,
+            spl_name_service: &spl_name_service::ID,
+            registrar: &registry_key,
+            sub_domain_account: &sub_domain_key,
+            domain_owner: &bob.pubkey(),
+            sub_record: &subrecord_key,
+            mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
+        },
+        unregister::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+
+    let ix = unregister(
+        unregister::Accounts {
+            system_program: &system_program::ID,
+            spl_name_service: &spl_name_service::ID,
+            registrar: &registry_key,
+            sub_domain_account: &sub_domain_key,
+            domain_owner: &bob.pubkey(),
+            sub_record: &subrecord_key,
+            mint_record: None,
+            spl_token_program: Some(&spl_token::ID),
+            vault: Some(&vault),
+            refund_destination: Some(&bob_ata),
+        },
+        unregister::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+
+    // Only the protocol fee is lost
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_ata)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    let price = 10_000_000;
+    assert_eq!(price_paid, price - (price * 5) / 100);
+    assert_eq!(token_account.amount, bob_balance - (price - price_paid));
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    assert_eq!(token_account.amount, 0);
+
+    // Bob registers a subdomain, the proceeds go to the vault
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let sub_domain_key_to_unreg_3 = sub_domain_key;
+    let (subrecord_key_to_unreg_3, _) =
+        SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: &vault,
+            fee_source: &bob_ata,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: None,
+            nft_metadata_account: None,
+            nft_mint_record: None,
+            sub_record: &subrecord_key_to_unreg_3,
+        },
+        register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let vault_balance = spl_token::state::Account::unpack(&acc.data[..])
+        .unwrap()
+        .amount;
+    assert!(vault_balance > 0);
+
+    // Bob cannot withdraw from the vault
+    let ix = withdraw(
+        withdraw::Accounts {
+            spl_token_program: &spl_token::ID,
+            registrar: &registry_key,
+            vault: &vault,
+            destination: &bob_ata,
+            authority: &bob.pubkey(),
+        },
+        withdraw::Params {
+            amount: vault_balance,
+        },
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+
+    // Alice withdraws the proceeds
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(*alice_fee_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let alice_balance = spl_token::state::Account::unpack(&acc.data[..])
+        .unwrap()
+        .amount;
+    let ix = withdraw(
+        withdraw::Accounts {
+            spl_token_program: &spl_token::ID,
+            registrar: &registry_key,
+            vault: &vault,
+            destination: alice_fee_account,
+            authority: &alice.pubkey(),
+        },
+        withdraw::Params {
+            amount: vault_balance,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(*alice_fee_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    assert_eq!(token_account.amount, alice_balance + vault_balance);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    assert_eq!(token_account.amount, 0);
+
+    // Alice adds a linear bonding curve: +10% per active sub, up to 2x
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: None,
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: None,
+            new_bonding_curve: Some(BondingCurve {
+                kind: CurveKind::Linear,
+                rate_bps: 1_000,
+                step: 0,
+                max_multiplier_bps: 20_000,
+            }),
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    let active_subs = registrar.total_sub_created;
+    assert!(active_subs > 0);
+
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let sub_domain_key_to_unreg_4 = sub_domain_key;
+    let (subrecord_key_to_unreg_4, _) =
+        SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let expected_price = 10_000_000 * (10_000 + 1_000 * active_subs) / 10_000;
+    assert_eq!(
+        sub_register::utils::get_registration_price(format!("\0{}", sub_domain), &registrar, false)
+            .unwrap(),
+        expected_price
+    );
+    // Quote the price on chain
+    let quote_ix = quote_price(
+        quote_price::Accounts {
+            registrar: &registry_key,
+            config: &Config::find_key(&sub_register::ID).0,
+            sub_domain_account: &sub_domain_key,
+            sub_record: &subrecord_key_to_unreg_4,
+            buyer: &bob.pubkey(),
+            nft_account: None,
+            nft_metadata_account: None,
+            nft_mint_record: None,
+        },
+        quote_price::Params {
+            domain: format!("\0{}", sub_domain),
+            irrevocable: false,
+        },
+    );
+    let (_, return_data) = simulate_instructions(&mut prg_test_ctx, vec![quote_ix.clone()], vec![])
+        .await
+        .unwrap();
+    let quote = quote_price::Quote::deserialize(&mut &return_data[..]).unwrap();
+    let protocol_fee = expected_price * 5 / 100;
+    assert_eq!(
+        quote,
+        quote_price::Quote {
+            price: expected_price,
+            registrar_proceeds: expected_price - protocol_fee,
+            protocol_fee,
+            eligibility: quote_price::Eligibility::Eligible,
+        }
+    );
+
+    let ix = quote_price(
+        quote_price::Accounts {
+            registrar: &registry_key,
+            config: &Config::find_key(&sub_register::ID).0,
+            sub_domain_account: &sub_domain_key,
+            sub_record: &subrecord_key_to_unreg_4,
+            buyer: &bob.pubkey(),
+            nft_account: None,
+            nft_metadata_account: None,
+            nft_mint_record: None,
+        },
+        quote_price::Params {
+            domain: format!("\0{}.", sub_domain),
+            irrevocable: false,
+        },
+    );
+    let (_, return_data) = simulate_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let quote = quote_price::Quote::deserialize(&mut &return_data[..]).unwrap();
+    assert_eq!(quote.eligibility, quote_price::Eligibility::InvalidName);
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_ata)
+        .await
+        .unwrap()
+        .unwrap();
+    let bob_balance = spl_token::state::Account::unpack(&acc.data[..])
+        .unwrap()
+        .amount;
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: &vault,
+            fee_source: &bob_ata,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: None,
+            nft_metadata_account: None,
+            nft_mint_record: None,
+            sub_record: &subrecord_key_to_unreg_4,
+        },
+        register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_ata)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    assert_eq!(token_account.amount, bob_balance - expected_price);
+
+    // The subdomain is no longer available
+    let (_, return_data) = simulate_instructions(&mut prg_test_ctx, vec![quote_ix], vec![])
+        .await
+        .unwrap();
+    let quote = quote_price::Quote::deserialize(&mut &return_data[..]).unwrap();
+    assert_eq!(
+        quote.eligibility,
+        quote_price::Eligibility::AlreadyRegistered
+    );
+
     // Unregister all domains
     sign_send_instructions(
         &mut prg_test_ctx,
@@ -425,6 +1097,9 @@ async fn test_functional() {
                 domain_owner: &alice.pubkey(),
                 sub_record: &subrecord_key_to_unreg_2,
                 mint_record: None,
+                spl_token_program: None,
+                vault: None,
+                refund_destination: None,
             },
             unregister::Params {},
         )],
@@ -443,6 +1118,71 @@ async fn test_functional() {
                 domain_owner: &bob.pubkey(),
                 sub_record: &subrecord_key_to_unreg_1,
                 mint_record: None,
+                spl_token_program: None,
+                vault: None,
+                refund_destination: None,
+            },
+            unregister::Params {},
+        )],
+        vec![&bob],
+    )
+    .await
+    .unwrap();
+
+    // The vault was emptied, the refund is capped to its balance
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_ata)
+        .await
+        .unwrap()
+        .unwrap();
+    let bob_balance = spl_token::state::Account::unpack(&acc.data[..])
+        .unwrap()
+        .amount;
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![unregister(
+            unregister::Accounts {
+                system_program: &system_program::ID,
+                spl_name_service: &spl_name_service::ID,
+                registrar: &registry_key,
+                sub_domain_account: &sub_domain_key_to_unreg_3,
+                domain_owner: &bob.pubkey(),
+                sub_record: &subrecord_key_to_unreg_3,
+                mint_record: None,
+                spl_token_program: Some(&spl_token::ID),
+                vault: Some(&vault),
+                refund_destination: Some(&bob_ata),
+            },
+            unregister::Params {},
+        )],
+        vec![&bob],
+    )
+    .await
+    .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bob_ata)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    assert_eq!(token_account.amount, bob_balance);
+
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![unregister(
+            unregister::Accounts {
+                system_program: &system_program::ID,
+                spl_name_service: &spl_name_service::ID,
+                registrar: &registry_key,
+                sub_domain_account: &sub_domain_key_to_unreg_4,
+                domain_owner: &bob.pubkey(),
+                sub_record: &subrecord_key_to_unreg_4,
+                mint_record: None,
+                spl_token_program: Some(&spl_token::ID),
+                vault: Some(&vault),
+                refund_destination: Some(&bob_ata),
             },
             unregister::Params {},
         )],
@@ -450,24 +1190,172 @@ async fn test_functional() {
     )
     .await
     .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(vault)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_account = spl_token::state::Account::unpack(&acc.data[..]).unwrap();
+    assert_eq!(token_account.amount, 0);
 
-    // Close registry
-    let ix = close_registrar(
-        close_registrar::Accounts {
+    // Reducing the refunds is timelocked
+    let (pending_change_key, _) = PendingChange::find_key(&registry_key, &sub_register::ID);
+    let edit_params = || edit_registrar::Params {
+        new_mint: None,
+        new_fee_account: None,
+        new_price_schedule: None,
+        new_max_nft_mint: None,
+        new_refund_policy: None,
+        new_bonding_curve: None,
+        new_soulbound: None,
+        new_timelock_delay: None,
+        new_allow_revoke: None,
+        new_revoke_expiry_time: None,
+        new_irrevocable_premium_bps: None,
+        new_nft_gated_collection: None,
+        new_nft_gate_policy: None,
+    };
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
             system_program: &system_program::ID,
+            authority: &alice.pubkey(),
             registrar: &registry_key,
-            domain_name_account: &name_key,
-            new_domain_owner: &alice.pubkey(),
-            lamports_target: &mint_authority.pubkey(),
-            registry_authority: &alice.pubkey(),
-            spl_name_program_id: &spl_name_service::ID,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_timelock_delay: Some(3_600),
+            ..edit_params()
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: Some(&pending_change_key),
+        },
+        edit_registrar::Params {
+            new_refund_policy: Some(RefundPolicy {
+                kind: RefundKind::ProRata,
+                window: 3_600,
+            }),
+            new_timelock_delay: Some(0),
+            ..edit_params()
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(
+        registrar.refund_policy,
+        RefundPolicy {
+            kind: RefundKind::Full,
+            window: 3_600,
+        }
+    );
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(pending_change_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let pending_change = PendingChange::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(
+        pending_change.change,
+        RegistrarChange {
+            new_refund_policy: Some(RefundPolicy {
+                kind: RefundKind::ProRata,
+                window: 3_600,
+            }),
+            new_timelock_delay: Some(0),
+            ..Default::default()
+        }
+    );
+
+    prg_test_ctx.warp_forward(3_600).await.unwrap();
+    let ix = execute_change(
+        execute_change::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            pending_change: &pending_change_key,
+            lamports_target: &alice.pubkey(),
+            fee_payer: &bob.pubkey(),
+        },
+        execute_change::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(registrar.refund_policy.kind, RefundKind::ProRata);
+    assert_eq!(registrar.timelock_delay, 0);
+
+    // The registry cannot be closed while the vault holds tokens
+    let ix =
+        spl_token::instruction::transfer(&spl_token::ID, &bob_ata, &vault, &bob.pubkey(), &[], 1)
+            .unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+    let close_ix = || {
+        close_registrar(
+            close_registrar::Accounts {
+                system_program: &system_program::ID,
+                registrar: &registry_key,
+                domain_name_account: &name_key,
+                new_domain_owner: &alice.pubkey(),
+                lamports_target: &mint_authority.pubkey(),
+                registry_authority: &alice.pubkey(),
+                spl_name_program_id: &spl_name_service::ID,
+                spl_token_program: &spl_token::ID,
+                vault: &vault,
+            },
+            close_registrar::Params {},
+        )
+    };
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![close_ix()], vec![&alice]).await;
+    assert!(res.is_err());
+
+    let ix = withdraw(
+        withdraw::Accounts {
+            spl_token_program: &spl_token::ID,
+            registrar: &registry_key,
+            vault: &vault,
+            destination: alice_fee_account,
+            authority: &alice.pubkey(),
         },
-        close_registrar::Params {},
+        withdraw::Params { amount: 1 },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
         .await
         .unwrap();
 
+    // Close registry, the vault is closed along with it
+    sign_send_instructions(&mut prg_test_ctx, vec![close_ix()], vec![&alice])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx.banks_client.get_account(vault).await.unwrap();
+    assert!(acc.is_none());
+
     ////////////////////////////////////////
     //
     // Test with NFT gated registrar
@@ -483,6 +1371,8 @@ async fn test_functional() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             allow_revoke: true,
@@ -536,6 +1426,7 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -543,25 +1434,76 @@ async fn test_functional() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
         .await
         .unwrap();
 
+    // Test: the NFT gating is checked against the recipient
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let mut ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: alice_fee_account,
+            fee_source: &bob_ata,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: Some(&bob_nft_account),
+            nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
+            sub_record: &subrecord_key,
+            nft_mint_record: Some(&mint_record),
+        },
+        register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: Some(alice.pubkey()),
+            irrevocable: false,
+        },
+    );
+    ix.accounts
+        .push(AccountMeta::new_readonly(alice.pubkey(), false));
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+
     // Test: edit registrar to remove NFT collection & register without NFT
     let ix = edit_registrar(
         edit_registrar::Accounts {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
-            new_authority: Some(alice.pubkey()),
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: None,
             new_max_nft_mint: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -594,6 +1536,7 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -601,6 +1544,8 @@ async fn test_functional() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -660,6 +1605,7 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -667,6 +1613,8 @@ async fn test_functional() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -683,6 +1631,7 @@ async fn test_functional() {
             sub_owner: &bob.pubkey(),
             parent_domain: &name_key,
             mint_record: Some(&mint_record),
+            delegate: None,
         },
         admin_revoke::Params {},
     );
@@ -718,6 +1667,7 @@ async fn test_functional() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -725,6 +1675,8 @@ async fn test_functional() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -0,0 +1,270 @@
+use spl_name_service::state::NameRecordHeader;
+use sub_register::{
+    entrypoint::process_instruction,
+    instruction::{admin_register, create_registrar},
+    state::{
+        config::Config,
+        registry::Registrar,
+        schedule::Price,
+        subdomain_record::{SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN},
+        ROOT_DOMAIN_ACCOUNT,
+    },
+    utils::{get_subdomain_key, get_subdomain_reverse},
+};
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{instruction::Instruction, program_pack::Pack, system_program, sysvar},
+    solana_program_test::{processor, ProgramTest},
+    solana_sdk::{
+        account::Account,
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    },
+};
+
+pub mod common;
+
+fn get_create_registrar_instruction(
+    name_key: &Pubkey,
+    ancestors: &[Pubkey],
+    owner: &Pubkey,
+    fee_payer: &Pubkey,
+) -> Instruction {
+    let (registry_key, _) = Registrar::find_key(name_key, &sub_register::ID);
+    create_registrar(
+        create_registrar::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            domain_name_account: name_key,
+            domain_owner: owner,
+            fee_payer,
+            spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: ancestors,
+        },
+        create_registrar::Params {
+            mint: Pubkey::new_unique(),
+            fee_account: Pubkey::new_unique(),
+            nft_gated_collection: None,
+            max_nft_mint: 0,
+            allow_revoke: true,
+            authority: *owner,
+            price_schedule: common::utils::serialize_price_schedule(&[Price {
+                length: 1,
+                price: 10_000_000,
+            }]),
+            revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+    )
+}
+
+fn get_admin_register_instruction(
+    sub_domain: &str,
+    parent_key: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let (registry_key, _) = Registrar::find_key(parent_key, &sub_register::ID);
+    let sub_domain_key = get_subdomain_key(sub_domain, parent_key);
+    let sub_reverse_key = get_subdomain_reverse(sub_domain, parent_key);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: parent_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            authority,
+            sub_record: &subrecord_key,
+            delegate: None,
+        },
+        admin_register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_nested_registrar() {
+    // Create program and test environment
+    use common::utils::sign_send_instructions;
+
+    // Alice owns company.sol and creates registrars on it and on dept.company.sol
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+
+    program_test.add_program("spl_name_service", spl_name_service::ID, None);
+    program_test.add_program("sns_registrar", sns_registrar::ID, None);
+
+    program_test.add_account(
+        alice.pubkey(),
+        Account {
+            lamports: 100_000_000_000,
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        sns_registrar::central_state::KEY,
+        Account {
+            lamports: 1_000_000,
+            owner: sns_registrar::ID,
+            data: vec![sns_registrar::central_state::NONCE],
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        ROOT_DOMAIN_ACCOUNT,
+        Account {
+            lamports: 1_000_000,
+            owner: spl_name_service::ID,
+            ..Account::default()
+        },
+    );
+
+    // Create mock .sol domain
+    let name_key = Keypair::new().pubkey();
+    let root_domain_data = NameRecordHeader {
+        parent_name: ROOT_DOMAIN_ACCOUNT,
+        owner: alice.pubkey(),
+        class: Pubkey::default(),
+    }
+    .try_to_vec()
+    .unwrap();
+    program_test.add_account(
+        name_key,
+        Account {
+            lamports: 1_000_000,
+            data: root_domain_data,
+            owner: spl_name_service::id(),
+            ..Account::default()
+        },
+    );
+
+    // Create a mock domain outside of .sol
+    let foreign_parent_key = Keypair::new().pubkey();
+    let foreign_key = Keypair::new().pubkey();
+    let foreign_domain_data = NameRecordHeader {
+        parent_name: foreign_parent_key,
+        owner: alice.pubkey(),
+        class: Pubkey::default(),
+    }
+    .try_to_vec()
+    .unwrap();
+    program_test.add_account(
+        foreign_key,
+        Account {
+            lamports: 1_000_000,
+            data: foreign_domain_data,
+            owner: spl_name_service::id(),
+            ..Account::default()
+        },
+    );
+
+    ////
+    // Create test context
+    ////
+    let mut prg_test_ctx = program_test.start_with_context().await;
+    let payer = prg_test_ctx.payer.pubkey();
+
+    // Domains outside of .sol are rejected
+    let ix = get_create_registrar_instruction(&foreign_key, &[], &alice.pubkey(), &payer);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Alice creates the registrar of company.sol and registers dept.company.sol
+    let ix = get_create_registrar_instruction(&name_key, &[], &alice.pubkey(), &payer);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let dept = "dept";
+    let ix = get_admin_register_instruction(dept, &name_key, &alice.pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let dept_key = get_subdomain_key(dept, &name_key);
+
+    // The parent domains are required for subdomains
+    let ix = get_create_registrar_instruction(&dept_key, &[], &alice.pubkey(), &payer);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // The parent domains must be the actual parents of the domain
+    let ix = get_create_registrar_instruction(&dept_key, &[foreign_key], &alice.pubkey(), &payer);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Alice creates the registrar of dept.company.sol
+    let ix = get_create_registrar_instruction(&dept_key, &[name_key], &alice.pubkey(), &payer);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let (dept_registry_key, _) = Registrar::find_key(&dept_key, &sub_register::ID);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(dept_registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(registrar.domain_account, dept_key);
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(dept_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.owner, dept_registry_key);
+
+    // Alice registers x.dept.company.sol
+    let sub_domain = "x";
+    let ix = get_admin_register_instruction(sub_domain, &dept_key, &alice.pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let sub_domain_key = get_subdomain_key(sub_domain, &dept_key);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(sub_domain_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.parent_name, dept_key);
+    assert_eq!(header.owner, alice.pubkey());
+
+    let sub_reverse_key = get_subdomain_reverse(sub_domain, &dept_key);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(sub_reverse_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.parent_name, dept_key);
+    assert_eq!(header.class, sns_registrar::central_state::KEY);
+
+    // Only the authority of the dept.company.sol registrar can register
+    let ix = get_admin_register_instruction("y", &dept_key, &bob.pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+}
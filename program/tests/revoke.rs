@@ -1,21 +1,32 @@
-use solana_program::{instruction::InstructionError, program_pack::Pack};
+use solana_program::{
+    clock::Clock,
+    instruction::{AccountMeta, InstructionError},
+    program_pack::Pack,
+};
 use solana_program_test::BanksClientError;
 use solana_sdk::transaction::TransactionError;
 use sub_register::{
     entrypoint::process_instruction,
     error::SubRegisterError,
-    instruction::{admin_register, admin_revoke, create_registrar, register, unregister},
+    instruction::{
+        accept_authority, admin_register, admin_revoke, admin_revoke_batch, admin_transfer,
+        create_registrar, edit_registrar, nft_owner_revoke, propose_authority, register,
+        revoke_expired, set_delegate, set_irrevocable, unregister,
+    },
     state::{
-        registry::Registrar,
+        config::Config,
+        delegate::{Delegate, Permission},
+        mint_record::MintRecord,
+        registry::{NftGatePolicy, Registrar},
         schedule::Price,
         subdomain_record::{SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN},
-        FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
+        Tag, FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
     },
 };
 
 use crate::common::utils::ProgramTestContextExtended;
 use {
-    borsh::BorshSerialize,
+    borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{system_program, sysvar},
     solana_program_test::{processor, ProgramTest},
     solana_sdk::{
@@ -25,6 +36,7 @@ use {
     },
     spl_associated_token_account::get_associated_token_address,
     spl_associated_token_account::instruction::create_associated_token_account,
+    spl_name_service::state::NameRecordHeader,
 };
 
 pub mod common;
@@ -190,6 +202,8 @@ async fn test_revoke_impersonation_safety() {
             domain_owner: &keypairs[ALICE].pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             mint,
@@ -238,6 +252,7 @@ async fn test_revoke_impersonation_safety() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &keypairs[BOB].pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -245,6 +260,8 @@ async fn test_revoke_impersonation_safety() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
@@ -261,6 +278,9 @@ async fn test_revoke_impersonation_safety() {
             domain_owner: &keypairs[BOB].pubkey(),
             sub_record: &subrecord_key,
             mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
         },
         unregister::Params {},
     );
@@ -286,6 +306,7 @@ async fn test_revoke_impersonation_safety() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &keypairs[ALICE].pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -293,6 +314,8 @@ async fn test_revoke_impersonation_safety() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
@@ -309,6 +332,9 @@ async fn test_revoke_impersonation_safety() {
             domain_owner: &keypairs[ALICE].pubkey(),
             sub_record: &subrecord_key,
             mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
         },
         unregister::Params {},
     );
@@ -339,6 +365,7 @@ async fn test_revoke_impersonation_safety() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &keypairs[BOB].pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -346,6 +373,8 @@ async fn test_revoke_impersonation_safety() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
@@ -364,6 +393,7 @@ async fn test_revoke_impersonation_safety() {
             sub_owner: &keypairs[BOB].pubkey(),
             parent_domain: &name_key,
             mint_record: None,
+            delegate: None,
         },
         admin_revoke::Params {},
     );
@@ -389,6 +419,7 @@ async fn test_revoke_impersonation_safety() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &keypairs[ALICE].pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -396,6 +427,8 @@ async fn test_revoke_impersonation_safety() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
@@ -423,9 +456,13 @@ async fn test_revoke_impersonation_safety() {
             sub_reverse_account: &sub_reverse_key,
             sub_record: &subrecord_key,
             authority: &keypairs[ALICE].pubkey(),
+            delegate: None,
         },
         admin_register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
         },
     );
     let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
@@ -460,6 +497,7 @@ async fn test_revoke_impersonation_safety() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &keypairs[ALICE].pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -467,9 +505,1391 @@ async fn test_revoke_impersonation_safety() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Alice provisions a staff subdomain for Charlie that lapses after an hour
+    let staff_domain = random_string();
+    let staff_key = sub_register::utils::get_subdomain_key(&staff_domain, &name_key);
+    let staff_reverse_key = sub_register::utils::get_subdomain_reverse(&staff_domain, &name_key);
+    let (staff_record_key, _) = SubDomainRecord::find_key(&staff_key, &sub_register::ID);
+    let clock: Clock = prg_test_ctx.banks_client.get_sysvar().await.unwrap();
+    let mut ix = admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &staff_key,
+            sub_reverse_account: &staff_reverse_key,
+            sub_record: &staff_record_key,
+            authority: &keypairs[ALICE].pubkey(),
+            delegate: None,
+        },
+        admin_register::Params {
+            domain: format!("\0{}", staff_domain),
+            recipient: Some(keypairs[CHARLIE].pubkey()),
+            expiry_timestamp: Some(clock.unix_timestamp + 3600),
+            allow_revoke: Some(false),
+        },
+    );
+    ix.accounts
+        .push(AccountMeta::new_readonly(keypairs[CHARLIE].pubkey(), false));
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(staff_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.owner, keypairs[CHARLIE].pubkey());
+
+    // The override prevents Alice from revoking it
+    let ix = admin_revoke(
+        admin_revoke::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &staff_key,
+            authority: &keypairs[ALICE].pubkey(),
+            spl_name_service: &spl_name_service::ID,
+            sub_record: &staff_record_key,
+            name_class: &Pubkey::default(),
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            mint_record: None,
+            delegate: None,
+        },
+        admin_revoke::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::CannotRevoke as u32)
+    }
+
+    // Bob cannot revoke it before the expiry
+    let ix = revoke_expired(
+        revoke_expired::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &staff_key,
+            sub_record: &staff_record_key,
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            mint_record: None,
+        },
+        revoke_expired::Params {},
+    );
+    let res =
+        sign_send_instructions(&mut prg_test_ctx, vec![ix.clone()], vec![&keypairs[BOB]]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::SubdomainNotExpired as u32)
+    }
+
+    // Anyone can revoke it once it lapsed
+    prg_test_ctx.warp_forward(3600).await.unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(staff_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let staff_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(staff_record.tag, Tag::RevokedSubRecord);
+
+    // Alice makes new subdomains soulbound
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &keypairs[ALICE].pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: None,
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: Some(true),
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Alice issues a soulbound subdomain to Charlie
+    let bound_domain = random_string();
+    let bound_key = sub_register::utils::get_subdomain_key(&bound_domain, &name_key);
+    let bound_reverse_key = sub_register::utils::get_subdomain_reverse(&bound_domain, &name_key);
+    let (bound_record_key, _) = SubDomainRecord::find_key(&bound_key, &sub_register::ID);
+    let mut ix = admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &bound_key,
+            sub_reverse_account: &bound_reverse_key,
+            sub_record: &bound_record_key,
+            authority: &keypairs[ALICE].pubkey(),
+            delegate: None,
+        },
+        admin_register::Params {
+            domain: format!("\0{}", bound_domain),
+            recipient: Some(keypairs[CHARLIE].pubkey()),
+            expiry_timestamp: None,
+            allow_revoke: Some(false),
+        },
+    );
+    ix.accounts
+        .push(AccountMeta::new_readonly(keypairs[CHARLIE].pubkey(), false));
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bound_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let bound_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(bound_record.bound_owner, Some(keypairs[CHARLIE].pubkey()));
+
+    // It cannot be revoked while Charlie holds it
+    let ix = revoke_expired(
+        revoke_expired::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &bound_key,
+            sub_record: &bound_record_key,
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            mint_record: None,
+        },
+        revoke_expired::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]]).await;
+    assert!(res.is_err());
+
+    // Charlie transfers it to Bob, anyone can now revoke it
+    let ix = spl_name_service::instruction::transfer(
+        spl_name_service::ID,
+        keypairs[BOB].pubkey(),
+        bound_key,
+        keypairs[CHARLIE].pubkey(),
+        None,
+    )
+    .unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[CHARLIE]])
+        .await
+        .unwrap();
+
+    let ix = revoke_expired(
+        revoke_expired::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &bound_key,
+            sub_record: &bound_record_key,
+            sub_owner: &keypairs[BOB].pubkey(),
+            parent_domain: &name_key,
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            mint_record: None,
+        },
+        revoke_expired::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(bound_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let bound_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(bound_record.tag, Tag::RevokedSubRecord);
+
+    // Bob buys soulbound subdomains, the records are sized for the bound owner
+    let get_register_ix = |sub_domain: &str| {
+        let sub_domain_key = sub_register::utils::get_subdomain_key(sub_domain, &name_key);
+        let sub_reverse_key = sub_register::utils::get_subdomain_reverse(sub_domain, &name_key);
+        let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+        let ix = register(
+            register::Accounts {
+                sns_registrar_program: &sns_registrar::ID,
+                system_program: &system_program::ID,
+                spl_token_program: &spl_token::ID,
+                spl_name_service: &spl_name_service::ID,
+                rent_sysvar: &sysvar::rent::id(),
+                root_domain: &ROOT_DOMAIN_ACCOUNT,
+                reverse_lookup_class: &sns_registrar::central_state::KEY,
+                fee_account: &atas[ALICE],
+                fee_source: &atas[BOB],
+                registrar: &registry_key,
+                parent_domain_account: &name_key,
+                sub_domain_account: &sub_domain_key,
+                sub_reverse_account: &sub_reverse_key,
+                fee_payer: &keypairs[BOB].pubkey(),
+                bonfida_fee_account,
+                config: &Config::find_key(&sub_register::ID).0,
+                nft_account: None,
+                nft_metadata_account: None,
+                sub_record: &subrecord_key,
+                nft_mint_record: None,
+            },
+            register::Params {
+                domain: format!("\0{}", sub_domain),
+                recipient: None,
+                irrevocable: false,
+            },
+        );
+        (ix, subrecord_key)
+    };
+
+    let immune_domain = random_string();
+    let immune_key = sub_register::utils::get_subdomain_key(&immune_domain, &name_key);
+    let (ix, immune_record_key) = get_register_ix(&immune_domain);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(immune_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(sub_record.bound_owner, Some(keypairs[BOB].pubkey()));
+
+    // The revoked record of the staff subdomain was not soulbound, it is resized
+    prg_test_ctx
+        .warp_forward(REVOKE_EXPIRY_DELAY_SECONDS_MIN)
+        .await
+        .unwrap();
+    let (ix, subrecord_key) = get_register_ix(&staff_domain);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(subrecord_key, staff_record_key);
+    let sub_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(sub_record.tag, Tag::SubRecord);
+    assert_eq!(sub_record.bound_owner, Some(keypairs[BOB].pubkey()));
+
+    // Alice makes the first one irrevocable, it survives a transfer by Bob
+    let ix = set_irrevocable(
+        set_irrevocable::Accounts {
+            registrar: &registry_key,
+            sub_record: &immune_record_key,
+            authority: &keypairs[ALICE].pubkey(),
         },
+        set_irrevocable::Params {},
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
         .await
         .unwrap();
+    let ix = spl_name_service::instruction::transfer(
+        spl_name_service::ID,
+        keypairs[CHARLIE].pubkey(),
+        immune_key,
+        keypairs[BOB].pubkey(),
+        None,
+    )
+    .unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+    let ix = revoke_expired(
+        revoke_expired::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &immune_key,
+            sub_record: &immune_record_key,
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            mint_record: None,
+        },
+        revoke_expired::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::CannotRevoke as u32)
+    }
+}
+
+#[tokio::test]
+async fn test_admin_actions() {
+    // Create program and test environment
+    use common::utils::{random_string, sign_send_instructions};
+
+    pub const NUMBER_OF_ACTORS: usize = 3;
+    // Owns the .sol, creates and administers the registry
+    pub const ALICE: usize = 0;
+    pub const BOB: usize = 1;
+    pub const CHARLIE: usize = 2;
+
+    let keypairs = (0..NUMBER_OF_ACTORS)
+        .map(|_| Keypair::new())
+        .collect::<Vec<_>>();
+
+    println!("[+] Alice key {}", keypairs[ALICE].pubkey());
+    println!("[+] Bob key {}", keypairs[BOB].pubkey());
+    println!("[+] Charlie key {}", keypairs[CHARLIE].pubkey());
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+
+    program_test.add_program("spl_name_service", spl_name_service::ID, None);
+    program_test.add_program("sns_registrar", sns_registrar::ID, None);
+
+    // Create and fund actor accounts
+    for k in &keypairs {
+        program_test.add_account(
+            k.pubkey(),
+            Account {
+                lamports: 100_000_000_000,
+                ..Account::default()
+            },
+        );
+    }
+
+    program_test.add_account(
+        ROOT_DOMAIN_ACCOUNT,
+        Account {
+            lamports: 1_000_000,
+            owner: spl_name_service::ID,
+            ..Account::default()
+        },
+    );
+
+    // Create mock .sol domain
+    let name_key = Keypair::new().pubkey();
+    println!("[+] Domain name key {}", name_key);
+
+    let root_domain_data = spl_name_service::state::NameRecordHeader {
+        parent_name: ROOT_DOMAIN_ACCOUNT,
+        owner: keypairs[ALICE].pubkey(),
+        class: Pubkey::default(),
+    }
+    .try_to_vec()
+    .unwrap();
+    program_test.add_account(
+        name_key,
+        Account {
+            lamports: 1_000_000,
+            data: root_domain_data,
+            owner: spl_name_service::id(),
+            ..Account::default()
+        },
+    );
+
+    //
+    // Create mint
+    //
+    let (mint, _) =
+        common::utils::mint_bootstrap(None, 6, &mut program_test, &keypairs[ALICE].pubkey());
+
+    ////
+    // Create test context
+    ////
+    let mut prg_test_ctx = program_test.start_with_context().await;
+
+    // Create ATAs
+    let instructions = keypairs
+        .iter()
+        .map(|k| {
+            create_associated_token_account(
+                &prg_test_ctx.payer.pubkey(),
+                &k.pubkey(),
+                &mint,
+                &spl_token::ID,
+            )
+        })
+        .collect();
+    sign_send_instructions(&mut prg_test_ctx, instructions, vec![])
+        .await
+        .unwrap();
+
+    let atas = keypairs
+        .iter()
+        .map(|k| get_associated_token_address(&k.pubkey(), &mint))
+        .collect::<Vec<_>>();
+
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        atas.iter()
+            .map(|a| {
+                spl_token::instruction::mint_to(
+                    &spl_token::ID,
+                    &mint,
+                    a,
+                    &keypairs[ALICE].pubkey(),
+                    &[],
+                    10_000_000_000,
+                )
+                .unwrap()
+            })
+            .collect(),
+        vec![&keypairs[ALICE]],
+    )
+    .await
+    .unwrap();
+
+    // Creates Bonfida fee account
+    let ix = create_associated_token_account(
+        &prg_test_ctx.payer.pubkey(),
+        &FEE_ACC_OWNER,
+        &mint,
+        &spl_token::ID,
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let bonfida_fee_account = &get_associated_token_address(&FEE_ACC_OWNER, &mint);
+
+    // Alice creates registry
+    let (registry_key, _) = Registrar::find_key(&name_key, &sub_register::ID);
+    println!("[+] Registry key {}", registry_key);
+
+    let ix = create_registrar(
+        create_registrar::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            domain_name_account: &name_key,
+            domain_owner: &keypairs[ALICE].pubkey(),
+            fee_payer: &prg_test_ctx.payer.pubkey(),
+            spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
+        },
+        create_registrar::Params {
+            mint,
+            fee_account: atas[ALICE],
+            nft_gated_collection: None,
+            max_nft_mint: 0,
+            allow_revoke: true,
+            authority: keypairs[ALICE].pubkey(),
+            price_schedule: common::utils::serialize_price_schedule(&[
+                Price {
+                    length: 1,
+                    price: 10_000_000,
+                },
+                Price {
+                    length: 2,
+                    price: 10_000_000,
+                },
+            ]),
+            revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Alice registers a subdomain for herself
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let ix = admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            sub_record: &subrecord_key,
+            authority: &keypairs[ALICE].pubkey(),
+            delegate: None,
+        },
+        admin_register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Alice recovers the subdomain to Charlie
+    let get_transfer_ix = |authority: &Pubkey| {
+        admin_transfer(
+            admin_transfer::Accounts {
+                registrar: &registry_key,
+                sub_domain_account: &sub_domain_key,
+                sub_record: &subrecord_key,
+                parent_domain: &name_key,
+                authority,
+                name_class: &Pubkey::default(),
+                spl_name_service: &spl_name_service::ID,
+                delegate: None,
+            },
+            admin_transfer::Params {
+                new_owner: keypairs[CHARLIE].pubkey(),
+            },
+        )
+    };
+    let ix = get_transfer_ix(&keypairs[BOB].pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]]).await;
+    assert!(res.is_err());
+
+    let ix = get_transfer_ix(&keypairs[ALICE].pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(sub_domain_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header = NameRecordHeader::unpack_from_slice(&acc.data).unwrap();
+    assert_eq!(header.owner, keypairs[CHARLIE].pubkey());
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(sub_record.tag, Tag::SubRecord);
+    assert_eq!(sub_record.owner, keypairs[CHARLIE].pubkey());
+
+    // Alice makes Bob an operator who can only register subdomains
+    let (delegate_key, _) =
+        Delegate::find_key(&registry_key, &keypairs[BOB].pubkey(), &sub_register::ID);
+    let get_set_delegate_ix = |permissions: u64| {
+        set_delegate(
+            set_delegate::Accounts {
+                system_program: &system_program::ID,
+                registrar: &registry_key,
+                authority: &keypairs[ALICE].pubkey(),
+                delegate_account: &delegate_key,
+            },
+            set_delegate::Params {
+                delegate: keypairs[BOB].pubkey(),
+                permissions,
+            },
+        )
+    };
+    let ix = get_set_delegate_ix(Permission::AdminRegister as u64);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    let delegated_domain = random_string();
+    let delegated_key = sub_register::utils::get_subdomain_key(&delegated_domain, &name_key);
+    let delegated_reverse_key =
+        sub_register::utils::get_subdomain_reverse(&delegated_domain, &name_key);
+    let (delegated_record_key, _) = SubDomainRecord::find_key(&delegated_key, &sub_register::ID);
+    let mut ix = admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &delegated_key,
+            sub_reverse_account: &delegated_reverse_key,
+            sub_record: &delegated_record_key,
+            authority: &keypairs[BOB].pubkey(),
+            delegate: Some(&delegate_key),
+        },
+        admin_register::Params {
+            domain: format!("\0{}", delegated_domain),
+            recipient: Some(keypairs[CHARLIE].pubkey()),
+            expiry_timestamp: None,
+            allow_revoke: None,
+        },
+    );
+    ix.accounts
+        .push(AccountMeta::new_readonly(keypairs[CHARLIE].pubkey(), false));
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+
+    // Bob cannot revoke without the permission
+    let ix = admin_revoke(
+        admin_revoke::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &delegated_key,
+            authority: &keypairs[BOB].pubkey(),
+            spl_name_service: &spl_name_service::ID,
+            sub_record: &delegated_record_key,
+            name_class: &Pubkey::default(),
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            mint_record: None,
+            delegate: Some(&delegate_key),
+        },
+        admin_revoke::Params {},
+    );
+    let res =
+        sign_send_instructions(&mut prg_test_ctx, vec![ix.clone()], vec![&keypairs[BOB]]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::MissingPermission as u32)
+    }
+
+    // Nor change the fee account
+    let ix_edit = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &keypairs[BOB].pubkey(),
+            registrar: &registry_key,
+            delegate: Some(&delegate_key),
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: Some(atas[BOB]),
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix_edit], vec![&keypairs[BOB]]).await;
+    assert!(res.is_err());
+
+    // Alice makes Bob a moderator
+    let set_ix = get_set_delegate_ix(Permission::AdminRegister as u64 | Permission::Revoke as u64);
+    sign_send_instructions(&mut prg_test_ctx, vec![set_ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+
+    // Alice removes the delegate
+    let ix = get_set_delegate_ix(0);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(delegate_key)
+        .await
+        .unwrap();
+    assert!(acc.is_none());
+
+    // Alice offers irrevocable subdomains at twice the price
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &keypairs[ALICE].pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: None,
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: Some(20_000),
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Bob buys an irrevocable subdomain
+    let get_balance = |data: &[u8]| spl_token::state::Account::unpack(data).unwrap().amount;
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(atas[BOB])
+        .await
+        .unwrap()
+        .unwrap();
+    let bob_balance = get_balance(&acc.data);
+    let premium_domain = random_string();
+    let premium_key = sub_register::utils::get_subdomain_key(&premium_domain, &name_key);
+    let premium_reverse_key =
+        sub_register::utils::get_subdomain_reverse(&premium_domain, &name_key);
+    let (premium_record_key, _) = SubDomainRecord::find_key(&premium_key, &sub_register::ID);
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: &atas[ALICE],
+            fee_source: &atas[BOB],
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &premium_key,
+            sub_reverse_account: &premium_reverse_key,
+            fee_payer: &keypairs[BOB].pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: None,
+            nft_metadata_account: None,
+            sub_record: &premium_record_key,
+            nft_mint_record: None,
+        },
+        register::Params {
+            domain: format!("\0{}", premium_domain),
+            recipient: None,
+            irrevocable: true,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(atas[BOB])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bob_balance - get_balance(&acc.data), 20_000_000);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(premium_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let premium_record = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
+    assert!(premium_record.irrevocable);
+
+    // Alice cannot revoke it
+    let get_revoke_ix = |sub_domain_account: &Pubkey, sub_record: &Pubkey, sub_owner: &Pubkey| {
+        admin_revoke(
+            admin_revoke::Accounts {
+                registrar: &registry_key,
+                sub_domain_account,
+                authority: &keypairs[ALICE].pubkey(),
+                spl_name_service: &spl_name_service::ID,
+                sub_record,
+                name_class: &Pubkey::default(),
+                sub_owner,
+                parent_domain: &name_key,
+                mint_record: None,
+                delegate: None,
+            },
+            admin_revoke::Params {},
+        )
+    };
+    let ix = get_revoke_ix(&premium_key, &premium_record_key, &keypairs[BOB].pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::CannotRevoke as u32)
+    }
+
+    // Alice grants immunity to the subdomain, now owned by Charlie
+    let ix = set_irrevocable(
+        set_irrevocable::Accounts {
+            registrar: &registry_key,
+            sub_record: &subrecord_key,
+            authority: &keypairs[ALICE].pubkey(),
+        },
+        set_irrevocable::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+    let ix = get_revoke_ix(&sub_domain_key, &subrecord_key, &keypairs[CHARLIE].pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
+    assert!(res.is_err());
+
+    // Alice makes Bob an operator again and hands the registrar over to Charlie
+    let ix = get_set_delegate_ix(Permission::AdminRegister as u64);
+    let ix_propose = propose_authority(
+        propose_authority::Accounts {
+            system_program: &system_program::ID,
+            authority: &keypairs[ALICE].pubkey(),
+            registrar: &registry_key,
+        },
+        propose_authority::Params {
+            new_authority: keypairs[CHARLIE].pubkey(),
+        },
+    );
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![ix, ix_propose],
+        vec![&keypairs[ALICE]],
+    )
+    .await
+    .unwrap();
+    let ix = accept_authority(
+        accept_authority::Accounts {
+            system_program: &system_program::ID,
+            new_authority: &keypairs[CHARLIE].pubkey(),
+            registrar: &registry_key,
+        },
+        accept_authority::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[CHARLIE]])
+        .await
+        .unwrap();
+
+    // The permissions granted by Alice lapsed
+    let operator_domain = random_string();
+    let operator_key = sub_register::utils::get_subdomain_key(&operator_domain, &name_key);
+    let operator_reverse_key =
+        sub_register::utils::get_subdomain_reverse(&operator_domain, &name_key);
+    let (operator_record_key, _) = SubDomainRecord::find_key(&operator_key, &sub_register::ID);
+    let ix = admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &operator_key,
+            sub_reverse_account: &operator_reverse_key,
+            sub_record: &operator_record_key,
+            authority: &keypairs[BOB].pubkey(),
+            delegate: Some(&delegate_key),
+        },
+        admin_register::Params {
+            domain: format!("\0{}", operator_domain),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
+        },
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::MissingPermission as u32)
+    }
+}
+
+#[tokio::test]
+async fn test_revocation_disabled() {
+    // Create program and test environment
+    use common::utils::{random_string, sign_send_instructions, simulate_instructions};
+
+    pub const NUMBER_OF_ACTORS: usize = 3;
+    // Owns the .sol, creates and administers the registry
+    pub const ALICE: usize = 0;
+    pub const BOB: usize = 1;
+    pub const CHARLIE: usize = 2;
+
+    let keypairs = (0..NUMBER_OF_ACTORS)
+        .map(|_| Keypair::new())
+        .collect::<Vec<_>>();
+
+    println!("[+] Alice key {}", keypairs[ALICE].pubkey());
+    println!("[+] Bob key {}", keypairs[BOB].pubkey());
+    println!("[+] Charlie key {}", keypairs[CHARLIE].pubkey());
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+
+    program_test.add_program("spl_name_service", spl_name_service::ID, None);
+    program_test.add_program("sns_registrar", sns_registrar::ID, None);
+    program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+
+    // Add mock NFT & collection, Bob holds the NFT
+    let mut data: Vec<u8> = vec![];
+    common::metadata::get_metadata()
+        .serialize(&mut data)
+        .unwrap();
+    program_test.add_account(
+        common::metadata::NFT_METADATA_KEY,
+        Account {
+            owner: mpl_token_metadata::ID,
+            lamports: 100_000_000_000,
+            data,
+            ..Account::default()
+        },
+    );
+    let mut data = [0; spl_token::state::Account::LEN];
+    common::metadata::get_nft_account(&keypairs[BOB].pubkey()).pack_into_slice(&mut data);
+    let bob_nft_account = Pubkey::new_unique();
+    program_test.add_account(
+        bob_nft_account,
+        Account {
+            owner: spl_token::ID,
+            lamports: 100_000_000_000,
+            data: data.into(),
+            ..Account::default()
+        },
+    );
+
+    // Create and fund actor accounts
+    for k in &keypairs {
+        program_test.add_account(
+            k.pubkey(),
+            Account {
+                lamports: 100_000_000_000,
+                ..Account::default()
+            },
+        );
+    }
+
+    program_test.add_account(
+        ROOT_DOMAIN_ACCOUNT,
+        Account {
+            lamports: 1_000_000,
+            owner: spl_name_service::ID,
+            ..Account::default()
+        },
+    );
+
+    // Create mock .sol domain
+    let name_key = Keypair::new().pubkey();
+    println!("[+] Domain name key {}", name_key);
+
+    let root_domain_data = spl_name_service::state::NameRecordHeader {
+        parent_name: ROOT_DOMAIN_ACCOUNT,
+        owner: keypairs[ALICE].pubkey(),
+        class: Pubkey::default(),
+    }
+    .try_to_vec()
+    .unwrap();
+    program_test.add_account(
+        name_key,
+        Account {
+            lamports: 1_000_000,
+            data: root_domain_data,
+            owner: spl_name_service::id(),
+            ..Account::default()
+        },
+    );
+
+    //
+    // Create mint
+    //
+    let (mint, _) =
+        common::utils::mint_bootstrap(None, 6, &mut program_test, &keypairs[ALICE].pubkey());
+
+    ////
+    // Create test context
+    ////
+    let mut prg_test_ctx = program_test.start_with_context().await;
+
+    // Create ATAs
+    let instructions = keypairs
+        .iter()
+        .map(|k| {
+            create_associated_token_account(
+                &prg_test_ctx.payer.pubkey(),
+                &k.pubkey(),
+                &mint,
+                &spl_token::ID,
+            )
+        })
+        .collect();
+    sign_send_instructions(&mut prg_test_ctx, instructions, vec![])
+        .await
+        .unwrap();
+
+    let atas = keypairs
+        .iter()
+        .map(|k| get_associated_token_address(&k.pubkey(), &mint))
+        .collect::<Vec<_>>();
+
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        atas.iter()
+            .map(|a| {
+                spl_token::instruction::mint_to(
+                    &spl_token::ID,
+                    &mint,
+                    a,
+                    &keypairs[ALICE].pubkey(),
+                    &[],
+                    10_000_000_000,
+                )
+                .unwrap()
+            })
+            .collect(),
+        vec![&keypairs[ALICE]],
+    )
+    .await
+    .unwrap();
+
+    // Creates Bonfida fee account
+    let ix = create_associated_token_account(
+        &prg_test_ctx.payer.pubkey(),
+        &FEE_ACC_OWNER,
+        &mint,
+        &spl_token::ID,
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let bonfida_fee_account = &get_associated_token_address(&FEE_ACC_OWNER, &mint);
+
+    // Alice creates a NFT gated registry
+    let (registry_key, _) = Registrar::find_key(&name_key, &sub_register::ID);
+    println!("[+] Registry key {}", registry_key);
+
+    let ix = create_registrar(
+        create_registrar::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            domain_name_account: &name_key,
+            domain_owner: &keypairs[ALICE].pubkey(),
+            fee_payer: &prg_test_ctx.payer.pubkey(),
+            spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
+        },
+        create_registrar::Params {
+            mint,
+            fee_account: atas[ALICE],
+            nft_gated_collection: Some(common::metadata::COLLECTION_KEY),
+            max_nft_mint: 2,
+            allow_revoke: true,
+            authority: keypairs[ALICE].pubkey(),
+            price_schedule: common::utils::serialize_price_schedule(&[
+                Price {
+                    length: 1,
+                    price: 10_000_000,
+                },
+                Price {
+                    length: 2,
+                    price: 10_000_000,
+                },
+            ]),
+            revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Bob registers a subdomain with his NFT
+    let nft_domain = random_string();
+    let nft_key = sub_register::utils::get_subdomain_key(&nft_domain, &name_key);
+    let nft_reverse_key = sub_register::utils::get_subdomain_reverse(&nft_domain, &name_key);
+    let (nft_record_key, _) = SubDomainRecord::find_key(&nft_key, &sub_register::ID);
+    let (mint_record_key, _) = MintRecord::find_key(
+        &common::metadata::NFT_MINT,
+        &registry_key,
+        &sub_register::ID,
+    );
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: &atas[ALICE],
+            fee_source: &atas[BOB],
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &nft_key,
+            sub_reverse_account: &nft_reverse_key,
+            fee_payer: &keypairs[BOB].pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: Some(&bob_nft_account),
+            nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
+            sub_record: &nft_record_key,
+            nft_mint_record: Some(&mint_record_key),
+        },
+        register::Params {
+            domain: format!("\0{}", nft_domain),
+            recipient: None,
+            irrevocable: false,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+
+    // Alice grants Charlie a subdomain that lapses after an hour
+    let clock: Clock = prg_test_ctx.banks_client.get_sysvar().await.unwrap();
+    let get_admin_register_ix = |sub_domain: &str, allow_revoke: Option<bool>| {
+        let sub_domain_key = sub_register::utils::get_subdomain_key(sub_domain, &name_key);
+        let sub_reverse_key = sub_register::utils::get_subdomain_reverse(sub_domain, &name_key);
+        let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+        let mut ix = admin_register(
+            admin_register::Accounts {
+                sns_registrar_program: &sns_registrar::ID,
+                system_program: &system_program::ID,
+                spl_token_program: &spl_token::ID,
+                spl_name_service: &spl_name_service::ID,
+                rent_sysvar: &sysvar::rent::id(),
+                root_domain: &ROOT_DOMAIN_ACCOUNT,
+                reverse_lookup_class: &sns_registrar::central_state::KEY,
+                registrar: &registry_key,
+                parent_domain_account: &name_key,
+                sub_domain_account: &sub_domain_key,
+                sub_reverse_account: &sub_reverse_key,
+                sub_record: &subrecord_key,
+                authority: &keypairs[ALICE].pubkey(),
+                delegate: None,
+            },
+            admin_register::Params {
+                domain: format!("\0{}", sub_domain),
+                recipient: Some(keypairs[CHARLIE].pubkey()),
+                expiry_timestamp: Some(clock.unix_timestamp + 3600),
+                allow_revoke,
+            },
+        );
+        ix.accounts
+            .push(AccountMeta::new_readonly(keypairs[CHARLIE].pubkey(), false));
+        ix
+    };
+    let staff_domain = random_string();
+    let staff_key = sub_register::utils::get_subdomain_key(&staff_domain, &name_key);
+    let (staff_record_key, _) = SubDomainRecord::find_key(&staff_key, &sub_register::ID);
+    let ix = get_admin_register_ix(&staff_domain, None);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+
+    // Alice makes the NFT subdomains revocable and disables revocation
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &keypairs[ALICE].pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_mint: None,
+            new_fee_account: None,
+            new_price_schedule: None,
+            new_max_nft_mint: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: Some(false),
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: Some(Some(Pubkey::new_unique())),
+            new_nft_gate_policy: Some(NftGatePolicy::Revocable),
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+        .await
+        .unwrap();
+    prg_test_ctx.warp_forward(3600).await.unwrap();
+
+    let assert_cannot_revoke = |res: Result<(), BanksClientError>| {
+        assert!(res.is_err());
+        if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(n),
+        ))) = res
+        {
+            assert_eq!(n, SubRegisterError::CannotRevoke as u32)
+        }
+    };
+
+    // Subdomains cannot opt into revocation
+    let ix = get_admin_register_ix(&random_string(), Some(true));
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
+    assert!(res.is_err());
+
+    // Alice cannot revoke the subdomain of Charlie
+    let ix = admin_revoke(
+        admin_revoke::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &staff_key,
+            authority: &keypairs[ALICE].pubkey(),
+            spl_name_service: &spl_name_service::ID,
+            sub_record: &staff_record_key,
+            name_class: &Pubkey::default(),
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            mint_record: None,
+            delegate: None,
+        },
+        admin_revoke::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
+    assert_cannot_revoke(res);
+
+    // Nor in a batch
+    let ix = admin_revoke_batch(
+        admin_revoke_batch::Accounts {
+            registrar: &registry_key,
+            parent_domain: &name_key,
+            authority: &keypairs[ALICE].pubkey(),
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            sub_accounts: &[staff_key, staff_record_key, keypairs[CHARLIE].pubkey()],
+            delegate: None,
+        },
+        admin_revoke_batch::Params {},
+    );
+    let (_, return_data) =
+        simulate_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]])
+            .await
+            .unwrap();
+    let results = Vec::<u64>::try_from_slice(&return_data).unwrap();
+    assert_eq!(results, vec![SubRegisterError::CannotRevoke as u64]);
+
+    // Disabling revocation does not extend it, anyone can revoke it once it lapsed
+    let ix = revoke_expired(
+        revoke_expired::Accounts {
+            registrar: &registry_key,
+            sub_domain_account: &staff_key,
+            sub_record: &staff_record_key,
+            sub_owner: &keypairs[CHARLIE].pubkey(),
+            parent_domain: &name_key,
+            name_class: &Pubkey::default(),
+            spl_name_service: &spl_name_service::ID,
+            mint_record: None,
+        },
+        revoke_expired::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(staff_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(acc.data[0], Tag::RevokedSubRecord as u8);
+
+    // Nobody can revoke the NFT subdomain although the NFT left the gated collection
+    let get_nft_owner_revoke_ix = |nft_owner: &Pubkey| {
+        nft_owner_revoke(
+            nft_owner_revoke::Accounts {
+                registrar: &registry_key,
+                sub_domain_account: &nft_key,
+                sub_record: &nft_record_key,
+                sub_owner: &keypairs[BOB].pubkey(),
+                parent_domain: &name_key,
+                nft_account: &bob_nft_account,
+                nft_metadata: &common::metadata::NFT_METADATA_KEY,
+                nft_owner,
+                name_class: &Pubkey::default(),
+                nft_mint_record: &mint_record_key,
+                spl_name_service: &spl_name_service::ID,
+            },
+            nft_owner_revoke::Params {},
+        )
+    };
+    let ix = get_nft_owner_revoke_ix(&keypairs[ALICE].pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[ALICE]]).await;
+    assert!(res.is_err());
+
+    // The NFT holder can still reclaim it
+    let ix = get_nft_owner_revoke_ix(&keypairs[BOB].pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&keypairs[BOB]])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(nft_record_key)
+        .await
+        .unwrap();
+    assert!(acc.is_none());
 }
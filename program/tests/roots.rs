@@ -0,0 +1,189 @@
+use spl_name_service::state::NameRecordHeader;
+use sub_register::{
+    entrypoint::process_instruction,
+    instruction::{admin_register, create_registrar},
+    state::{
+        config::{Config, RootDomain},
+        registry::Registrar,
+        schedule::Price,
+        subdomain_record::{SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN},
+        DEFAULT_FEE_BPS, FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
+    },
+    utils::{get_subdomain_key, get_subdomain_reverse_with_class},
+};
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{system_program, sysvar},
+    solana_program_test::{processor, ProgramTest},
+    solana_sdk::{
+        account::Account,
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    },
+};
+
+pub mod common;
+
+#[tokio::test]
+async fn test_root_domains() {
+    // Create program and test environment
+    use common::utils::sign_send_instructions;
+
+    // Alice owns domains under another TLD than .sol
+    let alice = Keypair::new();
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+
+    program_test.add_program("spl_name_service", spl_name_service::ID, None);
+
+    program_test.add_account(
+        alice.pubkey(),
+        Account {
+            lamports: 100_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    // Create mock TLD roots, only the first one is allowed by the config
+    let root_domain = RootDomain {
+        name_account: Pubkey::new_unique(),
+        registrar_program: Pubkey::new_unique(),
+        reverse_lookup_class: Pubkey::new_unique(),
+    };
+    let other_root_key = Pubkey::new_unique();
+
+    let (config_key, config_nonce) = Config::find_key(&sub_register::ID);
+    let mut config = Config::new(
+        &FEE_ACC_OWNER,
+        DEFAULT_FEE_BPS,
+        &FEE_ACC_OWNER,
+        config_nonce,
+    );
+    config.root_domains.push(root_domain);
+    program_test.add_account(
+        config_key,
+        Account {
+            lamports: 1_000_000,
+            data: config.try_to_vec().unwrap(),
+            owner: sub_register::ID,
+            ..Account::default()
+        },
+    );
+
+    // Create mock domains under both roots
+    let name_key = Keypair::new().pubkey();
+    let other_name_key = Keypair::new().pubkey();
+    for (key, parent) in [
+        (name_key, root_domain.name_account),
+        (other_name_key, other_root_key),
+    ] {
+        let domain_data = NameRecordHeader {
+            parent_name: parent,
+            owner: alice.pubkey(),
+            class: Pubkey::default(),
+        }
+        .try_to_vec()
+        .unwrap();
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data: domain_data,
+                owner: spl_name_service::id(),
+                ..Account::default()
+            },
+        );
+    }
+
+    ////
+    // Create test context
+    ////
+    let mut prg_test_ctx = program_test.start_with_context().await;
+
+    let create_registrar_ix = |name_key: &Pubkey, fee_payer: &Pubkey| {
+        create_registrar(
+            create_registrar::Accounts {
+                system_program: &system_program::ID,
+                registrar: &Registrar::find_key(name_key, &sub_register::ID).0,
+                domain_name_account: name_key,
+                domain_owner: &alice.pubkey(),
+                fee_payer,
+                spl_name_program_id: &spl_name_service::ID,
+                config: &config_key,
+                ancestor_domains: &[],
+            },
+            create_registrar::Params {
+                mint: Pubkey::new_unique(),
+                fee_account: Pubkey::new_unique(),
+                nft_gated_collection: None,
+                max_nft_mint: 0,
+                allow_revoke: true,
+                authority: alice.pubkey(),
+                price_schedule: common::utils::serialize_price_schedule(&[Price {
+                    length: 1,
+                    price: 10_000_000,
+                }]),
+                revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+            },
+        )
+    };
+    let payer = prg_test_ctx.payer.pubkey();
+
+    // Roots which are not allowed by the config are rejected
+    let ix = create_registrar_ix(&other_name_key, &payer);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Alice creates a registrar under the allowed root
+    let ix = create_registrar_ix(&name_key, &payer);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let (registry_key, _) = Registrar::find_key(&name_key, &sub_register::ID);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(registrar.root_domain, root_domain);
+
+    // Subdomains must be registered with the accounts of the root of the registrar
+    let sub_domain = "sub";
+    let sub_domain_key = get_subdomain_key(sub_domain, &name_key);
+    let sub_reverse_key =
+        get_subdomain_reverse_with_class(sub_domain, &name_key, &root_domain.reverse_lookup_class);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let ix = admin_register(
+        admin_register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            authority: &alice.pubkey(),
+            sub_record: &subrecord_key,
+            delegate: None,
+        },
+        admin_register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            expiry_timestamp: None,
+            allow_revoke: None,
+        },
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+}
@@ -1,19 +1,35 @@
 //! Tests of state integrity
 
 use crate::common::utils::ProgramTestContextExtended;
-use solana_program::program_pack::Pack;
+use solana_program::{
+    clock::Clock,
+    instruction::{AccountMeta, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::transaction::TransactionError;
 use sub_register::{
     entrypoint::process_instruction,
+    error::SubRegisterError,
     instruction::{
-        admin_register, admin_revoke, close_registrar, create_registrar, delete_subdomain_record,
-        edit_registrar, nft_owner_revoke, register, unregister,
+        accept_authority, admin_register, admin_revoke, cancel_authority_transfer, cancel_change,
+        close_registrar, create_registrar, delete_subdomain_record, edit_registrar, execute_change,
+        migrate, migrate_registrar, nft_owner_revoke, propose_authority, register, set_irrevocable,
+        sync_owner, unregister, wind_down,
     },
     state::{
-        mint_record::MintRecord,
-        registry::Registrar,
+        bonding_curve::BondingCurve,
+        config::{Config, RootDomain},
+        mint_record::{LegacyMintRecord, MintRecord},
+        pending_change::{PendingChange, RegistrarChange},
+        registry::{
+            LegacyRegistrar, NftGatePolicy, RefundPolicy, Registrar, WIND_DOWN_DELAY_SECONDS_MIN,
+        },
         schedule::Price,
-        subdomain_record::{SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN},
-        Tag, FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
+        subdomain_record::{
+            LegacySubDomainRecord, SubDomainRecord, REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+        Tag, Versioned, FEE_ACC_OWNER, ROOT_DOMAIN_ACCOUNT,
     },
     utils::get_subdomain_key,
 };
@@ -212,6 +228,8 @@ async fn test_state() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             max_nft_mint: 0,
@@ -250,6 +268,7 @@ async fn test_state() {
         allow_revoke: false,
         nft_gated_collection: None,
         tag: Tag::Registrar,
+        version: Registrar::VERSION,
         nonce,
         authority: alice.pubkey(),
         fee_account: *alice_fee_account,
@@ -267,8 +286,19 @@ async fn test_state() {
             },
         ],
         revoke_expiry_time: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        refund_policy: RefundPolicy::default(),
+        bonding_curve: BondingCurve::default(),
+        soulbound: false,
+        pending_authority: None,
+        timelock_delay: 0,
+        irrevocable_premium_bps: 0,
+        nft_gate_policy: NftGatePolicy::Grandfathered,
+        wind_down_deadline: None,
+        created_at: registrar.created_at,
+        root_domain: RootDomain::default(),
     };
     assert_eq!(registrar, expected_registrar);
+    assert!(registrar.created_at > 0);
 
     // Increase vec size
     let price_schedule = vec![
@@ -290,13 +320,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: Some(common::utils::serialize_price_schedule(&price_schedule)),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -342,13 +382,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: Some(common::utils::serialize_price_schedule(&price_schedule)),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -381,13 +431,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: Some(new_mint),
             new_fee_account: None,
             new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -410,13 +470,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: Some(mint),
             new_fee_account: None,
             new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -440,13 +510,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: None,
             new_fee_account: Some(new_fee_account),
             new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -468,13 +548,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: None,
             new_fee_account: Some(*alice_fee_account),
             new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -498,16 +588,67 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: Some(5),
-            new_authority: Some(new_authority.pubkey()),
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
-    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+    let ix_propose = propose_authority(
+        propose_authority::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+        },
+        propose_authority::Params {
+            new_authority: new_authority.pubkey(),
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix, ix_propose], vec![&alice])
+        .await
+        .unwrap();
+    // Verify state, the authority is unchanged until accepted
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    expected_registrar.pending_authority = Some(new_authority.pubkey());
+    expected_registrar.max_nft_mint = 5;
+    assert_eq!(registrar, expected_registrar);
+
+    // Only the pending authority can accept
+    let get_accept_ix = |new_authority: &Pubkey| {
+        accept_authority(
+            accept_authority::Accounts {
+                system_program: &system_program::ID,
+                new_authority,
+                registrar: &registry_key,
+            },
+            accept_authority::Params {},
+        )
+    };
+    let ix = get_accept_ix(&bob.pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob]).await;
+    assert!(res.is_err());
+
+    let ix = get_accept_ix(&new_authority.pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&new_authority])
         .await
         .unwrap();
     // Verify state
@@ -519,27 +660,58 @@ async fn test_state() {
         .unwrap();
     let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
     expected_registrar.authority = new_authority.pubkey();
-    expected_registrar.max_nft_mint = 5;
+    expected_registrar.pending_authority = None;
+    assert_eq!(registrar, expected_registrar);
+
+    // A mistyped authority can be cancelled
+    let ix = propose_authority(
+        propose_authority::Accounts {
+            system_program: &system_program::ID,
+            authority: &new_authority.pubkey(),
+            registrar: &registry_key,
+        },
+        propose_authority::Params {
+            new_authority: Pubkey::new_unique(),
+        },
+    );
+    let ix_cancel = cancel_authority_transfer(
+        cancel_authority_transfer::Accounts {
+            system_program: &system_program::ID,
+            authority: &new_authority.pubkey(),
+            registrar: &registry_key,
+        },
+        cancel_authority_transfer::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix, ix_cancel], vec![&new_authority])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
     assert_eq!(registrar, expected_registrar);
 
     // Change authority back to alice
-    let ix = edit_registrar(
-        edit_registrar::Accounts {
+    let ix = propose_authority(
+        propose_authority::Accounts {
             system_program: &system_program::ID,
             authority: &new_authority.pubkey(),
             registrar: &registry_key,
         },
-        edit_registrar::Params {
-            new_max_nft_mint: None,
-            new_authority: Some(alice.pubkey()),
-            new_mint: None,
-            new_fee_account: None,
-            new_price_schedule: None,
+        propose_authority::Params {
+            new_authority: alice.pubkey(),
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&new_authority])
         .await
         .unwrap();
+    let ix = get_accept_ix(&alice.pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
     // Verify state
     let acc = prg_test_ctx
         .banks_client
@@ -574,6 +746,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -581,6 +754,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -607,6 +782,9 @@ async fn test_state() {
             domain_owner: &bob.pubkey(),
             sub_record: &subrecord_key,
             mint_record: None,
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
         },
         unregister::Params {},
     );
@@ -664,13 +842,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: None,
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: Some(common::utils::serialize_price_schedule(&price_schedule)),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -731,6 +919,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -738,6 +927,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -788,6 +979,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -795,6 +987,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -818,7 +1012,13 @@ async fn test_state() {
     let subrecord: SubDomainRecord = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
     assert_eq!(
         subrecord,
-        SubDomainRecord::new(registry_key, sub_domain_key, bob.pubkey())
+        SubDomainRecord {
+            registered_at: subrecord.registered_at,
+            price_paid: 8_000_000 - (8_000_000 * 5) / 100,
+            owner: bob.pubkey(),
+            last_owner_sync: subrecord.registered_at,
+            ..SubDomainRecord::new(registry_key, sub_domain_key, bob.pubkey())
+        }
     );
 
     // Verify fees received
@@ -859,6 +1059,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: None,
             nft_metadata_account: None,
             sub_record: &subrecord_key,
@@ -866,6 +1067,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -889,7 +1092,13 @@ async fn test_state() {
     let subrecord: SubDomainRecord = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
     assert_eq!(
         subrecord,
-        SubDomainRecord::new(registry_key, sub_domain_key, bob.pubkey())
+        SubDomainRecord {
+            registered_at: subrecord.registered_at,
+            price_paid: 7_000_000 - (7_000_000 * 5) / 100,
+            owner: bob.pubkey(),
+            last_owner_sync: subrecord.registered_at,
+            ..SubDomainRecord::new(registry_key, sub_domain_key, bob.pubkey())
+        }
     );
 
     // Verify fees received
@@ -914,6 +1123,9 @@ async fn test_state() {
             unregister(
                 unregister::Accounts {
                     mint_record: None,
+                    spl_token_program: None,
+                    vault: None,
+                    refund_destination: None,
                     system_program: &system_program::ID,
                     spl_name_service: &spl_name_service::ID,
                     registrar: &registry_key,
@@ -930,6 +1142,9 @@ async fn test_state() {
             unregister(
                 unregister::Accounts {
                     mint_record: None,
+                    spl_token_program: None,
+                    vault: None,
+                    refund_destination: None,
                     system_program: &system_program::ID,
                     spl_name_service: &spl_name_service::ID,
                     registrar: &registry_key,
@@ -946,6 +1161,9 @@ async fn test_state() {
             unregister(
                 unregister::Accounts {
                     mint_record: None,
+                    spl_token_program: None,
+                    vault: None,
+                    refund_destination: None,
                     system_program: &system_program::ID,
                     spl_name_service: &spl_name_service::ID,
                     registrar: &registry_key,
@@ -996,9 +1214,13 @@ async fn test_state() {
                 sub_reverse_account: &sub_reverse_key,
                 authority: &alice.pubkey(),
                 sub_record: &subrecord_key,
+                delegate: None,
             },
             admin_register::Params {
                 domain: format!("\0{}", sub_domain),
+                recipient: None,
+                expiry_timestamp: None,
+                allow_revoke: None,
             },
         )],
         vec![&alice],
@@ -1025,7 +1247,12 @@ async fn test_state() {
     let subrecord: SubDomainRecord = SubDomainRecord::deserialize(&mut &acc.data[..]).unwrap();
     assert_eq!(
         subrecord,
-        SubDomainRecord::new(registry_key, sub_domain_key, alice.pubkey())
+        SubDomainRecord {
+            registered_at: subrecord.registered_at,
+            owner: alice.pubkey(),
+            last_owner_sync: subrecord.registered_at,
+            ..SubDomainRecord::new(registry_key, sub_domain_key, alice.pubkey())
+        }
     );
 
     // Unregister admin created sub
@@ -1034,6 +1261,9 @@ async fn test_state() {
         vec![unregister(
             unregister::Accounts {
                 mint_record: None,
+                spl_token_program: None,
+                vault: None,
+                refund_destination: None,
                 system_program: &system_program::ID,
                 spl_name_service: &spl_name_service::ID,
                 registrar: &registry_key,
@@ -1073,6 +1303,8 @@ async fn test_state() {
             lamports_target: &mint_authority.pubkey(),
             registry_authority: &alice.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            spl_token_program: &spl_token::ID,
+            vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
         },
         close_registrar::Params {},
     );
@@ -1106,6 +1338,8 @@ async fn test_state() {
             domain_owner: &alice.pubkey(),
             fee_payer: &prg_test_ctx.payer.pubkey(),
             spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
         },
         create_registrar::Params {
             max_nft_mint: 4,
@@ -1130,11 +1364,19 @@ async fn test_state() {
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
         .await
         .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
     let mut expected_registrar = Registrar {
         max_nft_mint: 4,
         allow_revoke: true,
         nft_gated_collection: Some(common::metadata::COLLECTION_KEY),
         tag: Tag::Registrar,
+        version: Registrar::VERSION,
         nonce,
         authority: alice.pubkey(),
         fee_account: *alice_fee_account,
@@ -1152,14 +1394,17 @@ async fn test_state() {
             },
         ],
         revoke_expiry_time: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        refund_policy: RefundPolicy::default(),
+        bonding_curve: BondingCurve::default(),
+        soulbound: false,
+        pending_authority: None,
+        timelock_delay: 0,
+        irrevocable_premium_bps: 0,
+        nft_gate_policy: NftGatePolicy::Grandfathered,
+        wind_down_deadline: None,
+        created_at: registrar.created_at,
+        root_domain: RootDomain::default(),
     };
-    let acc = prg_test_ctx
-        .banks_client
-        .get_account(registry_key)
-        .await
-        .unwrap()
-        .unwrap();
-    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
     assert_eq!(expected_registrar, registrar);
 
     // Test: edit the registrar
@@ -1190,13 +1435,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: Some(5),
-            new_authority: Some(alice.pubkey()),
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: Some(common::utils::serialize_price_schedule(&price_schedule)),
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -1240,13 +1495,23 @@ async fn test_state() {
             system_program: &system_program::ID,
             authority: &alice.pubkey(),
             registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
         },
         edit_registrar::Params {
             new_max_nft_mint: None,
-            new_authority: Some(alice.pubkey()),
             new_mint: None,
             new_fee_account: None,
             new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
@@ -1262,6 +1527,174 @@ async fn test_state() {
     let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
     assert_eq!(registrar, expected_registrar);
 
+    ////////////////////////////////////////
+    //
+    // Test timelocked edits
+    //
+    ////////////////////////////////////////
+
+    let (pending_change_key, _) = PendingChange::find_key(&registry_key, &sub_register::ID);
+    let edit_params = || edit_registrar::Params {
+        new_max_nft_mint: None,
+        new_mint: None,
+        new_fee_account: None,
+        new_price_schedule: None,
+        new_refund_policy: None,
+        new_bonding_curve: None,
+        new_soulbound: None,
+        new_timelock_delay: None,
+        new_allow_revoke: None,
+        new_revoke_expiry_time: None,
+        new_irrevocable_premium_bps: None,
+        new_nft_gated_collection: None,
+        new_nft_gate_policy: None,
+    };
+
+    // Increasing the timelock is immediate
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_timelock_delay: Some(3600),
+            ..edit_params()
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    expected_registrar.timelock_delay = 3600;
+    assert_eq!(registrar, expected_registrar);
+
+    // A more expensive schedule is queued
+    let mut expensive_schedule = price_schedule.clone();
+    expensive_schedule[0].price = 20_000_000;
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: Some(&pending_change_key),
+        },
+        edit_registrar::Params {
+            new_price_schedule: Some(common::utils::serialize_price_schedule(&expensive_schedule)),
+            ..edit_params()
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(registrar, expected_registrar);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(pending_change_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let pending_change = PendingChange::deserialize(&mut &acc.data[..]).unwrap();
+    assert_eq!(pending_change.tag, Tag::PendingChange);
+    assert_eq!(pending_change.version, PendingChange::VERSION);
+    assert_eq!(pending_change.payer, alice.pubkey());
+    assert_eq!(
+        pending_change.change,
+        RegistrarChange {
+            new_price_schedule: Some(expensive_schedule),
+            ..Default::default()
+        }
+    );
+
+    // Cancel the change
+    let ix = cancel_change(
+        cancel_change::Accounts {
+            registrar: &registry_key,
+            authority: &alice.pubkey(),
+            pending_change: &pending_change_key,
+            lamports_target: &alice.pubkey(),
+        },
+        cancel_change::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(pending_change_key)
+        .await
+        .unwrap();
+    assert!(acc.is_none());
+
+    // Disabling the timelock is itself timelocked
+    let ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: Some(&pending_change_key),
+        },
+        edit_registrar::Params {
+            new_timelock_delay: Some(0),
+            ..edit_params()
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let execute_ix = || {
+        execute_change(
+            execute_change::Accounts {
+                system_program: &system_program::ID,
+                registrar: &registry_key,
+                pending_change: &pending_change_key,
+                lamports_target: &alice.pubkey(),
+                fee_payer: &bob.pubkey(),
+            },
+            execute_change::Params {},
+        )
+    };
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![execute_ix()], vec![&bob]).await;
+    assert!(res.is_err());
+
+    prg_test_ctx.warp_forward(3600).await.unwrap();
+    sign_send_instructions(&mut prg_test_ctx, vec![execute_ix()], vec![&bob])
+        .await
+        .unwrap();
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar: Registrar = Registrar::deserialize(&mut &acc.data[..]).unwrap();
+    expected_registrar.timelock_delay = 0;
+    assert_eq!(registrar, expected_registrar);
+    let acc = prg_test_ctx
+        .banks_client
+        .get_account(pending_change_key)
+        .await
+        .unwrap();
+    assert!(acc.is_none());
+
     ////////////////////////////////////////
     //
     // Test how revoke instruction affect the state
@@ -1297,6 +1730,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1304,6 +1738,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1319,6 +1755,7 @@ async fn test_state() {
     let mint_record = MintRecord::deserialize(&mut &account.data[..]).unwrap();
     let mut expected_mint_record = MintRecord {
         tag: Tag::MintRecord,
+        version: MintRecord::VERSION,
         count: 1,
         mint: common::metadata::NFT_MINT,
     };
@@ -1333,11 +1770,20 @@ async fn test_state() {
     let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
     let expected_sub_record = SubDomainRecord {
         tag: Tag::SubRecord,
+        version: SubDomainRecord::VERSION,
         registrar: registry_key,
         mint_record: Some(mint_record_key),
         sub_key: sub_domain_key,
         expiry_timestamp: i64::MAX,
         allocator: bob.pubkey(),
+        registered_at: sub_record.registered_at,
+        price_paid: sub_record.price_paid,
+        allow_revoke: None,
+        bound_owner: None,
+        owner: bob.pubkey(),
+        last_owner_sync: sub_record.registered_at,
+        transfer_count: 0,
+        irrevocable: false,
     };
     assert_eq!(sub_record, expected_sub_record);
 
@@ -1369,6 +1815,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1376,6 +1823,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1413,6 +1862,7 @@ async fn test_state() {
             sub_owner: &bob.pubkey(),
             parent_domain: &name_key,
             mint_record: Some(&mint_record_key),
+            delegate: None,
         },
         admin_revoke::Params {},
     );
@@ -1555,6 +2005,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1562,6 +2013,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1597,6 +2050,9 @@ async fn test_state() {
             domain_owner: &bob.pubkey(),
             sub_record: &subrecord_key,
             mint_record: Some(&mint_record_key),
+            spl_token_program: None,
+            vault: None,
+            refund_destination: None,
         },
         unregister::Params {},
     );
@@ -1651,6 +2107,7 @@ async fn test_state() {
             sub_reverse_account: &sub_reverse_key,
             fee_payer: &bob.pubkey(),
             bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
             nft_account: Some(&bob_nft_account),
             nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
             sub_record: &subrecord_key,
@@ -1658,6 +2115,8 @@ async fn test_state() {
         },
         register::Params {
             domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
     );
     sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
@@ -1727,20 +2186,1057 @@ async fn test_state() {
     expected_registrar.total_sub_created -= 1;
     assert_eq!(registrar, expected_registrar);
 
-    // // Close registrar
-    let ix = close_registrar(
-        close_registrar::Accounts {
+    ////////////////////////////////////////
+    //
+    // Test gated collection changes
+    //
+    ////////////////////////////////////////
+
+    // Bob registers a subdomain with his NFT
+    let sub_domain = random_string();
+    let sub_domain_key = sub_register::utils::get_subdomain_key(&sub_domain, &name_key);
+    let sub_reverse_key = sub_register::utils::get_subdomain_reverse(&sub_domain, &name_key);
+    let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
             system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: alice_fee_account,
+            fee_source: &bob_ata,
             registrar: &registry_key,
-            domain_name_account: &name_key,
-            new_domain_owner: &alice.pubkey(),
-            lamports_target: &mint_authority.pubkey(),
-            registry_authority: &alice.pubkey(),
-            spl_name_program_id: &spl_name_service::ID,
+            parent_domain_account: &name_key,
+            sub_domain_account: &sub_domain_key,
+            sub_reverse_account: &sub_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: Some(&bob_nft_account),
+            nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
+            sub_record: &subrecord_key,
+            nft_mint_record: Some(&mint_record_key),
+        },
+        register::Params {
+            domain: format!("\0{}", sub_domain),
+            recipient: None,
+            irrevocable: false,
         },
-        close_registrar::Params {},
     );
-    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&bob])
+        .await
+        .unwrap();
+    expected_mint_record.count += 1;
+    expected_registrar.total_sub_created += 1;
+
+    // Bob registers another subdomain with his NFT, Alice makes it irrevocable
+    let immune_domain = random_string();
+    let immune_key = sub_register::utils::get_subdomain_key(&immune_domain, &name_key);
+    let immune_reverse_key = sub_register::utils::get_subdomain_reverse(&immune_domain, &name_key);
+    let (immune_record_key, _) = SubDomainRecord::find_key(&immune_key, &sub_register::ID);
+    let ix = register(
+        register::Accounts {
+            sns_registrar_program: &sns_registrar::ID,
+            system_program: &system_program::ID,
+            spl_token_program: &spl_token::ID,
+            spl_name_service: &spl_name_service::ID,
+            rent_sysvar: &sysvar::rent::id(),
+            root_domain: &ROOT_DOMAIN_ACCOUNT,
+            reverse_lookup_class: &sns_registrar::central_state::KEY,
+            fee_account: alice_fee_account,
+            fee_source: &bob_ata,
+            registrar: &registry_key,
+            parent_domain_account: &name_key,
+            sub_domain_account: &immune_key,
+            sub_reverse_account: &immune_reverse_key,
+            fee_payer: &bob.pubkey(),
+            bonfida_fee_account,
+            config: &Config::find_key(&sub_register::ID).0,
+            nft_account: Some(&bob_nft_account),
+            nft_metadata_account: Some(&common::metadata::NFT_METADATA_KEY),
+            sub_record: &immune_record_key,
+            nft_mint_record: Some(&mint_record_key),
+        },
+        register::Params {
+            domain: format!("\0{}", immune_domain),
+            recipient: None,
+            irrevocable: false,
+        },
+    );
+    let ix_immune = set_irrevocable(
+        set_irrevocable::Accounts {
+            registrar: &registry_key,
+            sub_record: &immune_record_key,
+            authority: &alice.pubkey(),
+        },
+        set_irrevocable::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix, ix_immune], vec![&bob, &alice])
         .await
         .unwrap();
+    expected_mint_record.count += 1;
+    expected_registrar.total_sub_created += 1;
+
+    let get_edit_ix = |params: edit_registrar::Params| {
+        edit_registrar(
+            edit_registrar::Accounts {
+                system_program: &system_program::ID,
+                authority: &alice.pubkey(),
+                registrar: &registry_key,
+                delegate: None,
+                pending_change: None,
+            },
+            params,
+        )
+    };
+
+    // Alice replaces the gated collection
+    let new_collection = Keypair::new().pubkey();
+    let ix = get_edit_ix(edit_registrar::Params {
+        new_nft_gated_collection: Some(Some(new_collection)),
+        ..edit_params()
+    });
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    expected_registrar.nft_gated_collection = Some(new_collection);
+    assert_eq!(registrar, expected_registrar);
+
+    // The subdomain of Bob is grandfathered, Alice cannot revoke it
+    let get_nft_revoke_ix = |sub_domain_account: &Pubkey, sub_record: &Pubkey| {
+        nft_owner_revoke(
+            nft_owner_revoke::Accounts {
+                registrar: &registry_key,
+                sub_domain_account,
+                sub_record,
+                sub_owner: &bob.pubkey(),
+                parent_domain: &name_key,
+                nft_account: &bob_nft_account,
+                nft_metadata: &common::metadata::NFT_METADATA_KEY,
+                nft_owner: &alice.pubkey(),
+                name_class: &Pubkey::default(),
+                nft_mint_record: &mint_record_key,
+                spl_name_service: &spl_name_service::ID,
+            },
+            nft_owner_revoke::Params {},
+        )
+    };
+    let res = sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![get_nft_revoke_ix(&sub_domain_key, &subrecord_key)],
+        vec![&alice],
+    )
+    .await;
+    assert!(res.is_err());
+
+    // Once the policy is revocable, anyone can revoke it
+    let ix = get_edit_ix(edit_registrar::Params {
+        new_nft_gate_policy: Some(NftGatePolicy::Revocable),
+        ..edit_params()
+    });
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![get_nft_revoke_ix(&sub_domain_key, &subrecord_key)],
+        vec![&alice],
+    )
+    .await
+    .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(sub_record.tag, Tag::RevokedSubRecord);
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(mint_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let mint_record = MintRecord::deserialize(&mut &account.data[..]).unwrap();
+    expected_mint_record.count -= 1;
+    assert_eq!(mint_record, expected_mint_record);
+
+    // Except for irrevocable subdomains
+    let ix = get_nft_revoke_ix(&immune_key, &immune_record_key);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(immune_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(sub_record.tag, Tag::SubRecord);
+
+    // The revoked subrecord can be deleted once expired
+    prg_test_ctx
+        .warp_forward(REVOKE_EXPIRY_DELAY_SECONDS_MIN)
+        .await
+        .unwrap();
+    let ix = delete_subdomain_record(
+        delete_subdomain_record::Accounts {
+            sub_domain: &sub_domain_key,
+            lamports_target: &bob.pubkey(),
+            sub_record: &subrecord_key,
+            mint_record: None,
+            registrar: &registry_key,
+        },
+        delete_subdomain_record::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    expected_registrar.total_sub_created -= 1;
+
+    // Alice removes the gating
+    let ix = get_edit_ix(edit_registrar::Params {
+        new_nft_gated_collection: Some(None),
+        new_nft_gate_policy: Some(NftGatePolicy::Grandfathered),
+        ..edit_params()
+    });
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    expected_registrar.nft_gated_collection = None;
+    assert_eq!(registrar, expected_registrar);
+
+    ////////////////////////////////////////
+    //
+    // Test revocation settings
+    //
+    ////////////////////////////////////////
+
+    // The revoke expiry delay cannot be shortened
+    let ix = get_edit_ix(edit_registrar::Params {
+        new_revoke_expiry_time: Some(REVOKE_EXPIRY_DELAY_SECONDS_MIN - 1),
+        ..edit_params()
+    });
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Lengthen the revoke expiry delay and disable revocation
+    let ix = get_edit_ix(edit_registrar::Params {
+        new_allow_revoke: Some(false),
+        new_revoke_expiry_time: Some(2 * REVOKE_EXPIRY_DELAY_SECONDS_MIN),
+        ..edit_params()
+    });
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    expected_registrar.allow_revoke = false;
+    expected_registrar.revoke_expiry_time = 2 * REVOKE_EXPIRY_DELAY_SECONDS_MIN;
+    assert_eq!(registrar, expected_registrar);
+
+    // Revocation cannot be enabled again
+    let ix = get_edit_ix(edit_registrar::Params {
+        new_allow_revoke: Some(true),
+        ..edit_params()
+    });
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    ////////////////////////////////////////
+    //
+    // Test wind-down
+    //
+    ////////////////////////////////////////
+
+    let get_admin_register_ix = |sub_domain: &str, allow_revoke: Option<bool>| {
+        let sub_domain_key = get_subdomain_key(sub_domain, &name_key);
+        let sub_reverse_key = sub_register::utils::get_subdomain_reverse(sub_domain, &name_key);
+        let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+        let ix = admin_register(
+            admin_register::Accounts {
+                sns_registrar_program: &sns_registrar::ID,
+                system_program: &system_program::ID,
+                spl_token_program: &spl_token::ID,
+                spl_name_service: &spl_name_service::ID,
+                rent_sysvar: &sysvar::rent::id(),
+                root_domain: &ROOT_DOMAIN_ACCOUNT,
+                reverse_lookup_class: &sns_registrar::central_state::KEY,
+                registrar: &registry_key,
+                parent_domain_account: &name_key,
+                sub_domain_account: &sub_domain_key,
+                sub_reverse_account: &sub_reverse_key,
+                authority: &alice.pubkey(),
+                sub_record: &subrecord_key,
+                delegate: None,
+            },
+            admin_register::Params {
+                domain: format!("\0{}", sub_domain),
+                recipient: None,
+                expiry_timestamp: None,
+                allow_revoke,
+            },
+        );
+        (ix, sub_domain_key, subrecord_key)
+    };
+    let get_delete_ix = |sub_domain_key: &Pubkey, subrecord_key: &Pubkey| {
+        delete_subdomain_record(
+            delete_subdomain_record::Accounts {
+                sub_domain: sub_domain_key,
+                lamports_target: &alice.pubkey(),
+                sub_record: subrecord_key,
+                mint_record: None,
+                registrar: &registry_key,
+            },
+            delete_subdomain_record::Params {},
+        )
+    };
+    let get_close_ix = || {
+        close_registrar(
+            close_registrar::Accounts {
+                system_program: &system_program::ID,
+                registrar: &registry_key,
+                domain_name_account: &name_key,
+                new_domain_owner: &alice.pubkey(),
+                lamports_target: &mint_authority.pubkey(),
+                registry_authority: &alice.pubkey(),
+                spl_name_program_id: &spl_name_service::ID,
+                spl_token_program: &spl_token::ID,
+                vault: &Registrar::find_vault_key(&registry_key, &sub_register::ID).0,
+            },
+            close_registrar::Params {},
+        )
+    };
+
+    // Subdomains cannot be revocable once revocation is disabled
+    let (ix, _, _) = get_admin_register_ix(&random_string(), Some(true));
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+
+    // Alice grants three subdomains
+    let mut live_subs = vec![];
+    for _ in 0..3 {
+        let (ix, sub_domain_key, subrecord_key) = get_admin_register_ix(&random_string(), None);
+        sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+            .await
+            .unwrap();
+        live_subs.push((sub_domain_key, subrecord_key));
+    }
+    expected_registrar.total_sub_created += 3;
+
+    // Subdomain owners get a minimum notice
+    let clock: Clock = prg_test_ctx.banks_client.get_sysvar().await.unwrap();
+    let get_wind_down_ix = |deadline: i64| {
+        wind_down(
+            wind_down::Accounts {
+                system_program: &system_program::ID,
+                authority: &alice.pubkey(),
+                registrar: &registry_key,
+            },
+            wind_down::Params { deadline },
+        )
+    };
+    let ix = get_wind_down_ix(clock.unix_timestamp + WIND_DOWN_DELAY_SECONDS_MIN - 1);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::WindDownDelayTooLow as u32)
+    }
+
+    let deadline = clock.unix_timestamp + WIND_DOWN_DELAY_SECONDS_MIN + 1;
+    let ix = get_wind_down_ix(deadline);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    expected_registrar.wind_down_deadline = Some(deadline);
+    assert_eq!(registrar, expected_registrar);
+
+    // Registrations are stopped
+    let (ix, _, _) = get_admin_register_ix(&random_string(), None);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::RegistrarWindingDown as u32)
+    }
+
+    // Before the deadline, the registrar cannot be closed and the subdomains are kept
+    let ix = get_close_ix();
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+    let (sub_domain_key, subrecord_key) = live_subs[0];
+    let ix = get_delete_ix(&sub_domain_key, &subrecord_key);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![]).await;
+    assert!(res.is_err());
+
+    // Once the deadline has passed, anyone can delete the records of live subdomains
+    prg_test_ctx
+        .warp_forward(WIND_DOWN_DELAY_SECONDS_MIN + 1)
+        .await
+        .unwrap();
+    let ix = get_delete_ix(&sub_domain_key, &subrecord_key);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    expected_registrar.total_sub_created -= 1;
+    assert_eq!(registrar, expected_registrar);
+
+    // The registrar can be closed with live subdomains
+    let ix = get_close_ix();
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    // The records of the remaining subdomains can still be deleted
+    let (sub_domain_key, subrecord_key) = live_subs[1];
+    let ix = get_delete_ix(&sub_domain_key, &subrecord_key);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap();
+    assert!(account.is_none());
+
+    ////////////////////////////////////////
+    //
+    // Test registrar migration
+    //
+    ////////////////////////////////////////
+
+    // Alice places the parent domain in a new registrar at the same address
+    let ix = create_registrar(
+        create_registrar::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            domain_name_account: &name_key,
+            domain_owner: &alice.pubkey(),
+            fee_payer: &prg_test_ctx.payer.pubkey(),
+            spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
+        },
+        create_registrar::Params {
+            max_nft_mint: 0,
+            allow_revoke: true,
+            nft_gated_collection: None,
+            mint,
+            fee_account: *alice_fee_account,
+            authority: alice.pubkey(),
+            price_schedule: common::utils::serialize_price_schedule(&[Price {
+                length: 1,
+                price: 10_000_000,
+            }]),
+            revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    // The record of the last subdomain belongs to the previous registrar
+    let (_, subrecord_key) = live_subs[2];
+    let ix = set_irrevocable(
+        set_irrevocable::Accounts {
+            registrar: &registry_key,
+            sub_record: &subrecord_key,
+            authority: &alice.pubkey(),
+        },
+        set_irrevocable::Params {},
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix.clone()], vec![&alice]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::StaleSubRecord as u32)
+    }
+
+    // Alice moves it to the new registrar
+    let get_migrate_registrar_ix = || {
+        migrate_registrar(
+            migrate_registrar::Accounts {
+                registrar: &registry_key,
+                sub_record: &subrecord_key,
+                authority: &alice.pubkey(),
+            },
+            migrate_registrar::Params {},
+        )
+    };
+    sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![get_migrate_registrar_ix()],
+        vec![&alice],
+    )
+    .await
+    .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(registrar.total_sub_created, 1);
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(sub_record.registered_at, registrar.created_at);
+    assert_eq!(sub_record.price_paid, 0);
+
+    // It can only be moved once and is now managed by the new registrar
+    let res = sign_send_instructions(
+        &mut prg_test_ctx,
+        vec![get_migrate_registrar_ix()],
+        vec![&alice],
+    )
+    .await;
+    assert!(res.is_err());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_parent_handover() {
+    use common::utils::{random_string, sign_send_instructions};
+
+    // Alice hands the administration of her registrar over to Carol, the parent domain stays in
+    // the registrar and the subdomain of Bob is kept
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+    program_test.add_program("spl_name_service", spl_name_service::ID, None);
+    program_test.add_program("sns_registrar", sns_registrar::ID, None);
+
+    for k in [&alice, &carol] {
+        program_test.add_account(
+            k.pubkey(),
+            Account {
+                lamports: 100_000_000_000,
+                ..Account::default()
+            },
+        );
+    }
+    program_test.add_account(
+        sns_registrar::central_state::KEY,
+        Account {
+            lamports: 1_000_000,
+            owner: sns_registrar::ID,
+            data: vec![sns_registrar::central_state::NONCE],
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        ROOT_DOMAIN_ACCOUNT,
+        Account {
+            lamports: 1_000_000,
+            owner: spl_name_service::ID,
+            ..Account::default()
+        },
+    );
+
+    // Create mock .sol domain
+    let name_key = Keypair::new().pubkey();
+    program_test.add_account(
+        name_key,
+        Account {
+            lamports: 1_000_000,
+            data: spl_name_service::state::NameRecordHeader {
+                parent_name: ROOT_DOMAIN_ACCOUNT,
+                owner: alice.pubkey(),
+                class: Pubkey::default(),
+            }
+            .try_to_vec()
+            .unwrap(),
+            owner: spl_name_service::id(),
+            ..Account::default()
+        },
+    );
+
+    let mut prg_test_ctx = program_test.start_with_context().await;
+
+    // Alice creates the registrar and grants a subdomain to Bob
+    let (registry_key, _) = Registrar::find_key(&name_key, &sub_register::ID);
+    let ix = create_registrar(
+        create_registrar::Accounts {
+            system_program: &system_program::ID,
+            registrar: &registry_key,
+            domain_name_account: &name_key,
+            domain_owner: &alice.pubkey(),
+            fee_payer: &prg_test_ctx.payer.pubkey(),
+            spl_name_program_id: &spl_name_service::ID,
+            config: &Config::find_key(&sub_register::ID).0,
+            ancestor_domains: &[],
+        },
+        create_registrar::Params {
+            mint: Pubkey::new_unique(),
+            fee_account: Pubkey::new_unique(),
+            nft_gated_collection: None,
+            max_nft_mint: 0,
+            allow_revoke: true,
+            authority: alice.pubkey(),
+            price_schedule: common::utils::serialize_price_schedule(&[Price {
+                length: 1,
+                price: 10_000_000,
+            }]),
+            revoke_expiry_delay: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    let get_admin_register_ix = |sub_domain: &str, authority: &Pubkey| {
+        let sub_domain_key = get_subdomain_key(sub_domain, &name_key);
+        let sub_reverse_key = sub_register::utils::get_subdomain_reverse(sub_domain, &name_key);
+        let (subrecord_key, _) = SubDomainRecord::find_key(&sub_domain_key, &sub_register::ID);
+        let mut ix = admin_register(
+            admin_register::Accounts {
+                sns_registrar_program: &sns_registrar::ID,
+                system_program: &system_program::ID,
+                spl_token_program: &spl_token::ID,
+                spl_name_service: &spl_name_service::ID,
+                rent_sysvar: &sysvar::rent::id(),
+                root_domain: &ROOT_DOMAIN_ACCOUNT,
+                reverse_lookup_class: &sns_registrar::central_state::KEY,
+                registrar: &registry_key,
+                parent_domain_account: &name_key,
+                sub_domain_account: &sub_domain_key,
+                sub_reverse_account: &sub_reverse_key,
+                authority,
+                sub_record: &subrecord_key,
+                delegate: None,
+            },
+            admin_register::Params {
+                domain: format!("\0{}", sub_domain),
+                recipient: Some(bob.pubkey()),
+                expiry_timestamp: None,
+                allow_revoke: None,
+            },
+        );
+        ix.accounts
+            .push(AccountMeta::new_readonly(bob.pubkey(), false));
+        (ix, sub_domain_key, subrecord_key)
+    };
+    let (ix, sub_domain_key, subrecord_key) =
+        get_admin_register_ix(&random_string(), &alice.pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+
+    // The handover does not require closing the registrar
+    let ix = propose_authority(
+        propose_authority::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+        },
+        propose_authority::Params {
+            new_authority: carol.pubkey(),
+        },
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice])
+        .await
+        .unwrap();
+    let ix = accept_authority(
+        accept_authority::Accounts {
+            system_program: &system_program::ID,
+            new_authority: &carol.pubkey(),
+            registrar: &registry_key,
+        },
+        accept_authority::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&carol])
+        .await
+        .unwrap();
+
+    // Verify state
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(registrar.authority, carol.pubkey());
+    assert_eq!(registrar.total_sub_created, 1);
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(name_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header =
+        spl_name_service::state::NameRecordHeader::unpack_from_slice(&account.data).unwrap();
+    assert_eq!(header.owner, registry_key);
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(sub_domain_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let header =
+        spl_name_service::state::NameRecordHeader::unpack_from_slice(&account.data).unwrap();
+    assert_eq!(header.owner, bob.pubkey());
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(subrecord_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(sub_record.tag, Tag::SubRecord);
+    assert_eq!(sub_record.registrar, registry_key);
+
+    // Only Carol administers the registrar
+    let (ix, _, _) = get_admin_register_ix(&random_string(), &alice.pubkey());
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&alice]).await;
+    assert!(res.is_err());
+    let (ix, _, _) = get_admin_register_ix(&random_string(), &carol.pubkey());
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![&carol])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_migrate() {
+    use common::utils::sign_send_instructions;
+
+    // Alice administers a registrar created before versioned layouts
+    let alice = Keypair::new();
+    let nft_mint = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::new(
+        "sub_register",
+        sub_register::ID,
+        processor!(process_instruction),
+    );
+    program_test.add_account(
+        alice.pubkey(),
+        Account {
+            lamports: 100_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let legacy_registrar = LegacyRegistrar {
+        tag: Tag::LegacyRegistrar,
+        nonce: 255,
+        authority: alice.pubkey(),
+        fee_account: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        domain_account: Pubkey::new_unique(),
+        total_sub_created: 42,
+        nft_gated_collection: Some(Pubkey::new_unique()),
+        max_nft_mint: 200,
+        allow_revoke: true,
+        price_schedule: vec![Price {
+            length: 1,
+            price: 10_000_000,
+        }],
+        revoke_expiry_time: REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+    };
+    let registry_key = Pubkey::new_unique();
+    program_test.add_account(
+        registry_key,
+        Account {
+            lamports: 100_000_000,
+            owner: sub_register::ID,
+            data: legacy_registrar.try_to_vec().unwrap(),
+            ..Account::default()
+        },
+    );
+
+    let legacy_mint_record = LegacyMintRecord {
+        tag: Tag::LegacyMintRecord,
+        count: 200,
+        mint: nft_mint,
+    };
+    let mint_record_key = Pubkey::new_unique();
+    program_test.add_account(
+        mint_record_key,
+        Account {
+            lamports: 100_000_000,
+            owner: sub_register::ID,
+            data: legacy_mint_record.try_to_vec().unwrap(),
+            ..Account::default()
+        },
+    );
+
+    let legacy_sub_record = LegacySubDomainRecord {
+        tag: Tag::LegacyRevokedSubRecord,
+        registrar: registry_key,
+        sub_key: Pubkey::new_unique(),
+        mint_record: Some(mint_record_key),
+        expiry_timestamp: 1_000,
+        allocator: alice.pubkey(),
+    };
+    let sub_record_key = Pubkey::new_unique();
+    program_test.add_account(
+        sub_record_key,
+        Account {
+            lamports: 100_000_000,
+            owner: sub_register::ID,
+            data: legacy_sub_record.try_to_vec().unwrap(),
+            ..Account::default()
+        },
+    );
+
+    // A live legacy subdomain owned by Alice
+    let legacy_sub_key = Pubkey::new_unique();
+    let legacy_live_sub_record = LegacySubDomainRecord {
+        tag: Tag::LegacySubRecord,
+        registrar: registry_key,
+        sub_key: legacy_sub_key,
+        mint_record: None,
+        expiry_timestamp: i64::MAX,
+        allocator: alice.pubkey(),
+    };
+    let live_sub_record_key = SubDomainRecord::find_key(&legacy_sub_key, &sub_register::ID).0;
+    program_test.add_account(
+        live_sub_record_key,
+        Account {
+            lamports: 100_000_000,
+            owner: sub_register::ID,
+            data: legacy_live_sub_record.try_to_vec().unwrap(),
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        legacy_sub_key,
+        Account {
+            lamports: 1_000_000,
+            owner: spl_name_service::ID,
+            data: spl_name_service::state::NameRecordHeader {
+                parent_name: legacy_registrar.domain_account,
+                owner: alice.pubkey(),
+                class: Pubkey::default(),
+            }
+            .try_to_vec()
+            .unwrap(),
+            ..Account::default()
+        },
+    );
+
+    let mut prg_test_ctx = program_test.start_with_context().await;
+
+    // Legacy accounts must be migrated before being written to
+    let edit_ix = edit_registrar(
+        edit_registrar::Accounts {
+            system_program: &system_program::ID,
+            authority: &alice.pubkey(),
+            registrar: &registry_key,
+            delegate: None,
+            pending_change: None,
+        },
+        edit_registrar::Params {
+            new_max_nft_mint: Some(1_000),
+            new_mint: None,
+            new_fee_account: None,
+            new_price_schedule: None,
+            new_refund_policy: None,
+            new_bonding_curve: None,
+            new_soulbound: None,
+            new_timelock_delay: None,
+            new_allow_revoke: None,
+            new_revoke_expiry_time: None,
+            new_irrevocable_premium_bps: None,
+            new_nft_gated_collection: None,
+            new_nft_gate_policy: None,
+        },
+    );
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![edit_ix.clone()], vec![&alice]).await;
+    assert!(res.is_err());
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(n),
+    ))) = res
+    {
+        assert_eq!(n, SubRegisterError::AccountNeedsMigration as u32)
+    }
+
+    // Anyone can migrate the registrar
+    let fee_payer = prg_test_ctx.payer.pubkey();
+    let migrate_ix = |account: &Pubkey| {
+        migrate(
+            migrate::Accounts {
+                system_program: &system_program::ID,
+                account,
+                fee_payer: &fee_payer,
+            },
+            migrate::Params {},
+        )
+    };
+    let ix = migrate_ix(&registry_key);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    let mut expected_registrar = Registrar::new(
+        &alice.pubkey(),
+        &legacy_registrar.fee_account,
+        &legacy_registrar.mint,
+        &legacy_registrar.domain_account,
+        legacy_registrar.price_schedule.clone(),
+        legacy_registrar.nonce,
+        legacy_registrar.nft_gated_collection,
+        200,
+        true,
+        REVOKE_EXPIRY_DELAY_SECONDS_MIN,
+    );
+    expected_registrar.total_sub_created = 42;
+    assert_eq!(registrar, expected_registrar);
+    assert_eq!(registrar.tag, Tag::Registrar);
+    assert_eq!(registrar.version, Registrar::VERSION);
+
+    // A migrated account cannot be migrated again
+    let ix = migrate_ix(&registry_key);
+    let res = sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![]).await;
+    assert!(res.is_err());
+
+    // The registrar can now be edited
+    sign_send_instructions(&mut prg_test_ctx, vec![edit_ix], vec![&alice])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(registry_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let registrar = Registrar::deserialize(&mut &account.data[..]).unwrap();
+    expected_registrar.max_nft_mint = 1_000;
+    assert_eq!(registrar, expected_registrar);
+
+    // Same for the mint record
+    let ix = migrate_ix(&mint_record_key);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(mint_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let mint_record = MintRecord::deserialize(&mut &account.data[..]).unwrap();
+    let expected_mint_record = MintRecord {
+        tag: Tag::MintRecord,
+        version: MintRecord::VERSION,
+        count: 200,
+        mint: nft_mint,
+    };
+    assert_eq!(mint_record, expected_mint_record);
+
+    // Same for the subdomain record, which keeps its state
+    let ix = migrate_ix(&sub_record_key);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(sub_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
+    let expected_sub_record = SubDomainRecord {
+        tag: Tag::RevokedSubRecord,
+        mint_record: Some(mint_record_key),
+        expiry_timestamp: 1_000,
+        ..SubDomainRecord::new(registry_key, legacy_sub_record.sub_key, alice.pubkey())
+    };
+    assert_eq!(sub_record, expected_sub_record);
+    assert_eq!(account.data.len(), sub_record.try_to_vec().unwrap().len());
+
+    // The owner of a legacy subdomain is recorded without counting a transfer
+    let ix = migrate_ix(&live_sub_record_key);
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let ix = sync_owner(
+        sync_owner::Accounts {
+            sub_domain_account: &legacy_sub_key,
+            sub_record: &live_sub_record_key,
+        },
+        sync_owner::Params {},
+    );
+    sign_send_instructions(&mut prg_test_ctx, vec![ix], vec![])
+        .await
+        .unwrap();
+    let account = prg_test_ctx
+        .banks_client
+        .get_account(live_sub_record_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let sub_record = SubDomainRecord::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(sub_record.owner, alice.pubkey());
+    assert_eq!(sub_record.transfer_count, 0);
 }